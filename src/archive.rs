@@ -0,0 +1,116 @@
+use crate::Task;
+use crate::task::Finished;
+use crate::task_status::Statuses;
+use borsh::BorshDeserialize;
+use borsh::BorshSerialize;
+use chrono::{DateTime, Local};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Paragraph};
+
+fn time_label(what: &str, seconds: i64) -> String {
+    DateTime::from_timestamp(seconds, 0)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format(&format!("{what} %Y-%m-%d %H:%M"))
+                .to_string()
+        })
+        .unwrap_or_else(|| format!("{what} at an unknown time"))
+}
+
+// a finished task taken out of the list, it keeps where it was so it can be put back
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ArchivedTask {
+    pub task: Task,
+    pub path: Vec<usize>,           // where the task was when it was archived
+    pub parent_titles: Vec<String>, // the tasks above it then, to check they're still there
+    pub archived_at: i64,           // unix seconds
+}
+
+impl ArchivedTask {
+    pub fn new(task: Task, path: Vec<usize>, parent_titles: Vec<String>) -> Self {
+        Self {
+            task,
+            path,
+            parent_titles,
+            archived_at: Local::now().timestamp(),
+        }
+    }
+
+    // when the task was finished, or archived for tasks finished before that was kept
+    pub fn time_label(&self) -> String {
+        match self.task.finished() {
+            Finished::At(finished_at) => time_label("finished", finished_at),
+            _ => time_label("archived", self.archived_at),
+        }
+    }
+
+    pub fn origin(&self) -> String {
+        match self.parent_titles.is_empty() {
+            true => "top level".to_string(),
+            false => self.parent_titles.join(" > "),
+        }
+    }
+}
+
+// lists the archive newest first, entries are numbered the same way on the command line
+#[derive(Default)]
+pub struct ArchiveBrowser {
+    pub selected: usize, // counted from the newest entry
+}
+
+impl ArchiveBrowser {
+    pub fn draw(&self, frame: &mut Frame, archive: &[ArchivedTask], statuses: &Statuses) {
+        let app_block = Block::bordered()
+            .title(Line::from(format!(" Archive, {} tasks ", archive.len())).bold())
+            .title_bottom(
+                Line::from(vec![
+                    " Choose Task ".into(),
+                    "<Up>/<Down>".green().bold(),
+                    " Restore ".into(),
+                    "<Enter/r>".green().bold(),
+                    " Back ".into(),
+                    "<Esc/q> ".green().bold(),
+                ])
+                .centered(),
+            );
+        let paintable_area = app_block.inner(frame.area());
+        frame.render_widget(app_block, frame.area());
+
+        let mut lines: Vec<Line> = archive
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, entry)| {
+                let subtasks = match entry.task.count() - 1 {
+                    0 => String::new(),
+                    1 => " +1 subtask".to_string(),
+                    count => format!(" +{count} subtasks"),
+                };
+                let mut line = Line::from(vec![
+                    format!(" {} ", i + 1).bold(),
+                    format!("{} ", entry.time_label()).dark_gray(),
+                    entry.task.title().to_string().into(),
+                    " ".into(),
+                    statuses.to_span(entry.task.status()),
+                    subtasks.dark_gray(),
+                    format!(" from {}", entry.origin()).dark_gray(),
+                ]);
+                if i == self.selected {
+                    line = line.cyan().bold();
+                }
+                line
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::from(
+                " nothing archived yet, <D> in the list archives finished tasks ",
+            ));
+        }
+        // keeps the selected entry on screen once the archive is longer than the window
+        let scroll = (self.selected + 1).saturating_sub(paintable_area.height as usize);
+        frame.render_widget(
+            Paragraph::new(lines).scroll((scroll as u16, 0)),
+            paintable_area,
+        );
+    }
+}
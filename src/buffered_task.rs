@@ -1,39 +1,89 @@
 use crate::Task;
-use crate::task_status::TaskStatus;
+use crate::error::Result;
+use crate::priority::Priority;
+use crate::tags;
+use crate::task::Finished;
+use crate::task_date::TaskDate;
+use crate::task_status::{Statuses, TaskStatus};
 use crate::text_input::TextInputField;
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
 use ratatui::widgets::Block;
 use ratatui::widgets::Paragraph;
 
-#[derive(Default)]
 pub struct BufferedTask {
     title: TextInputField,
     desc: TextInputField,
     status: TaskStatus,
+    priority: Priority,
+    due: TextInputField,
+    scheduled: TextInputField,
+    tags: TextInputField, // tags that aren't written in the title
     selected_field: SelectedField,
+    statuses: Statuses, // the list's statuses, for cycling and drawing the status field
+    message: Option<String>,
+    finished: Finished, // kept from the task being edited, the form doesn't show it
 }
 
 impl BufferedTask {
-    pub fn save(self) -> Option<Task> {
-        let Self {
-            title,
-            desc,
-            status,
-            ..
-        } = self;
-        Some(Task::new(
-            title.return_buffer(),
-            status,
-            desc.return_buffer(),
-        ))
+    pub fn new(statuses: &Statuses) -> Self {
+        Self {
+            title: TextInputField::default(),
+            desc: TextInputField::default().multi_line(),
+            status: statuses.initial(),
+            priority: Priority::None,
+            due: TextInputField::default(),
+            scheduled: TextInputField::default(),
+            tags: TextInputField::default(),
+            selected_field: SelectedField::default(),
+            statuses: statuses.clone(),
+            message: None,
+            finished: Finished::No,
+        }
+    }
+
+    pub fn from_task(task: &Task, statuses: &Statuses) -> Self {
+        let date_field = |date: Option<TaskDate>| {
+            TextInputField::from_string(&date.map(|date| date.to_string()).unwrap_or_default())
+        };
+        Self {
+            title: TextInputField::from_string(task.title()),
+            desc: TextInputField::from_string(task.desc()).multi_line(),
+            status: task.status(),
+            priority: task.priority(),
+            due: date_field(task.due()),
+            scheduled: date_field(task.scheduled()),
+            tags: TextInputField::from_string(&task.extra_tags().join(" ")),
+            selected_field: SelectedField::default(),
+            statuses: statuses.clone(),
+            message: None,
+            finished: task.finished(),
+        }
+    }
+
+    pub fn save(&self) -> Result<Task> {
+        let today = TaskDate::today();
+        let mut task = Task::new(self.title.text(), self.status, self.desc.text());
+        task.set_priority(self.priority);
+        task.add_tags(&tags::parse_tag_list(&self.tags.text()));
+        task.set_due(TaskDate::parse(&self.due.text(), today)?);
+        task.set_scheduled(TaskDate::parse(&self.scheduled.text(), today)?);
+        task.set_finished(self.finished);
+        Ok(task)
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some(message);
     }
 
     pub fn draw(&self, frame: &mut Frame) {
-        let app_block = Block::bordered().title_bottom(
+        let mut app_block = Block::bordered().title_bottom(
             Line::from(vec![
                 " Change Field ".into(),
                 "<Up>/<Down>".green().bold(),
-                " Change Status ".into(),
+                " New Line In Description ".into(),
+                "<Enter>".green().bold(),
+                " Change Status/Priority ".into(),
                 "<Left>/<Right>".green().bold(),
                 " Cancel ".into(),
                 "<ctrl+c>".green().bold(),
@@ -42,12 +92,15 @@ impl BufferedTask {
             ])
             .centered(),
         );
+        if let Some(message) = &self.message {
+            app_block = app_block.title(Line::from(format!(" {message} ")).red().bold());
+        }
 
         let paintable_area = app_block.inner(frame.area());
 
         let vertical = Layout::vertical([
             Constraint::Fill(1),
-            Constraint::Length(6),
+            Constraint::Length(22),
             Constraint::Fill(1),
         ]);
         let [_title_area, vertical_main_area, _status_area] = vertical.areas(paintable_area);
@@ -59,89 +112,153 @@ impl BufferedTask {
         let [_left_half, main_area, _right_area] = main_horizontal.areas(vertical_main_area);
 
         let horizontal = Layout::horizontal([Constraint::Length(14), Constraint::Fill(1)]);
+        // single line fields get a blank row under them, the description gets the rest
         let main_area_app = Layout::vertical([
-            Constraint::Fill(1),
-            Constraint::Fill(1),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Length(2),
             Constraint::Fill(1),
         ]);
 
-        let [title_edit, status_edit, desc_edit] = main_area_app.areas(main_area);
+        let [
+            title_edit,
+            status_edit,
+            priority_edit,
+            due_edit,
+            scheduled_edit,
+            tags_edit,
+            desc_edit,
+        ] = main_area_app.areas(main_area);
 
         let [title_left_area, title_right_area] = horizontal.areas(title_edit);
         let [status_left_area, status_right_area] = horizontal.areas(status_edit);
+        let [priority_left_area, priority_right_area] = horizontal.areas(priority_edit);
+        let [due_left_area, due_right_area] = horizontal.areas(due_edit);
+        let [scheduled_left_area, scheduled_right_area] = horizontal.areas(scheduled_edit);
+        let [tags_left_area, tags_right_area] = horizontal.areas(tags_edit);
         let [desc_left_area, desc_right_area] = horizontal.areas(desc_edit);
 
-        match self.selected_field {
-            SelectedField::Title => (
-                frame.render_widget(
-                    Paragraph::new("-Title-").style(Style::default().bg(Color::DarkGray)),
-                    title_left_area,
-                ),
-                frame.render_widget(
-                    Paragraph::new(" Status ").style(Style::default().bg(Color::DarkGray)),
-                    status_left_area,
-                ),
-                frame.render_widget(
-                    Paragraph::new(" Description ").style(Style::default().bg(Color::DarkGray)),
-                    desc_left_area,
-                ),
-            ),
-            SelectedField::Desc => (
-                frame.render_widget(
-                    Paragraph::new(" Title ").style(Style::default().bg(Color::DarkGray)),
-                    title_left_area,
-                ),
-                frame.render_widget(
-                    Paragraph::new(" Status ").style(Style::default().bg(Color::DarkGray)),
-                    status_left_area,
-                ),
-                frame.render_widget(
-                    Paragraph::new("-Description-").style(Style::default().bg(Color::DarkGray)),
-                    desc_left_area,
-                ),
-            ),
-            SelectedField::Status => (
-                frame.render_widget(
-                    Paragraph::new(" Title ").style(Style::default().bg(Color::DarkGray)),
-                    title_left_area,
-                ),
-                frame.render_widget(
-                    Paragraph::new("-Status-").style(Style::default().bg(Color::DarkGray)),
-                    status_left_area,
-                ),
-                frame.render_widget(
-                    Paragraph::new(" Description ").style(Style::default().bg(Color::DarkGray)),
-                    desc_left_area,
-                ),
-            ),
-        };
+        self.draw_label(frame, "Title", SelectedField::Title, title_left_area);
+        self.draw_label(frame, "Status", SelectedField::Status, status_left_area);
+        self.draw_label(
+            frame,
+            "Priority",
+            SelectedField::Priority,
+            priority_left_area,
+        );
+        self.draw_label(frame, "Due", SelectedField::Due, due_left_area);
+        self.draw_label(
+            frame,
+            "Scheduled",
+            SelectedField::Scheduled,
+            scheduled_left_area,
+        );
+        self.draw_label(frame, "Tags", SelectedField::Tags, tags_left_area);
+        self.draw_label(frame, "Description", SelectedField::Desc, desc_left_area);
 
-        match self.selected_field {
-            SelectedField::Title => {
-                frame.render_widget(self.status.as_paragraph(), status_right_area);
-                self.desc.draw_unselected(frame, desc_right_area);
-                self.title.draw_selected(frame, title_right_area);
-            }
-            SelectedField::Status => {
-                self.title.draw_unselected(frame, title_right_area);
-                frame.render_widget(self.status.as_paragraph_selected(), status_right_area);
-                self.desc.draw_unselected(frame, desc_right_area);
-            }
-            SelectedField::Desc => {
-                self.title.draw_unselected(frame, title_right_area);
-                frame.render_widget(self.status.as_paragraph(), status_right_area);
-                self.desc.draw_selected(frame, desc_right_area);
-            }
+        let status = match self.selected_field {
+            SelectedField::Status => self.statuses.as_paragraph_selected(self.status),
+            _ => self.statuses.as_paragraph(self.status),
         };
+        frame.render_widget(status, status_right_area);
+        frame.render_widget(
+            self.priority
+                .to_line(self.selected_field == SelectedField::Priority),
+            priority_right_area,
+        );
+        self.draw_field(frame, &self.title, SelectedField::Title, title_right_area);
+        self.draw_date_field(frame, &self.due, SelectedField::Due, due_right_area);
+        self.draw_date_field(
+            frame,
+            &self.scheduled,
+            SelectedField::Scheduled,
+            scheduled_right_area,
+        );
+        self.draw_field(frame, &self.tags, SelectedField::Tags, tags_right_area);
+        self.desc.draw_area(
+            frame,
+            desc_right_area,
+            self.selected_field == SelectedField::Desc,
+        );
 
         frame.render_widget(app_block, frame.area());
         // let area = centered_rect(60, 25, f.area());
     }
 
+    fn draw_label(&self, frame: &mut Frame, label: &str, field: SelectedField, area: Rect) {
+        let label = match self.selected_field == field {
+            true => format!("-{label}-"),
+            false => format!(" {label} "),
+        };
+        // the description's label stays the height of the others
+        let area = Rect {
+            height: area.height.min(2),
+            ..area
+        };
+        frame.render_widget(
+            Paragraph::new(label).style(Style::default().bg(Color::DarkGray)),
+            area,
+        );
+    }
+
+    fn draw_field(
+        &self,
+        frame: &mut Frame,
+        input: &TextInputField,
+        field: SelectedField,
+        area: Rect,
+    ) {
+        match self.selected_field == field {
+            true => input.draw_selected(frame, area),
+            false => input.draw_unselected(frame, area),
+        }
+    }
+
+    // date fields show what the typed text will be saved as next to it
+    fn draw_date_field(
+        &self,
+        frame: &mut Frame,
+        input: &TextInputField,
+        field: SelectedField,
+        area: Rect,
+    ) {
+        let [input_area, parsed_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(28)]).areas(area);
+        self.draw_field(frame, input, field, input_area);
+        let today = TaskDate::today();
+        let parsed = match TaskDate::parse(&input.text(), today) {
+            Ok(Some(date)) if date.describe(today) == date.to_string() => {
+                Line::from(date.to_string()).dark_gray()
+            }
+            Ok(Some(date)) => Line::from(format!("{date} ({})", date.describe(today))).dark_gray(),
+            Ok(None) => Line::from("no date").dark_gray(),
+            Err(_) => Line::from("today, fri, +3d, 2026-11-01").red(),
+        };
+        frame.render_widget(parsed.right_aligned(), parsed_area);
+    }
+
+    fn selected_input(&mut self) -> Option<&mut TextInputField> {
+        match self.selected_field {
+            SelectedField::Title => Some(&mut self.title),
+            SelectedField::Due => Some(&mut self.due),
+            SelectedField::Scheduled => Some(&mut self.scheduled),
+            SelectedField::Tags => Some(&mut self.tags),
+            SelectedField::Desc => Some(&mut self.desc),
+            SelectedField::Status | SelectedField::Priority => None,
+        }
+    }
+
     pub fn next_field(&mut self) {
         self.selected_field = match self.selected_field {
             SelectedField::Title => SelectedField::Desc,
-            SelectedField::Desc => SelectedField::Status,
+            SelectedField::Desc => SelectedField::Tags,
+            SelectedField::Tags => SelectedField::Scheduled,
+            SelectedField::Scheduled => SelectedField::Due,
+            SelectedField::Due => SelectedField::Priority,
+            SelectedField::Priority => SelectedField::Status,
             SelectedField::Status => SelectedField::Title,
         };
     }
@@ -149,58 +266,91 @@ impl BufferedTask {
     pub fn prev_field(&mut self) {
         self.selected_field = match self.selected_field {
             SelectedField::Title => SelectedField::Status,
+            SelectedField::Status => SelectedField::Priority,
+            SelectedField::Priority => SelectedField::Due,
+            SelectedField::Due => SelectedField::Scheduled,
+            SelectedField::Scheduled => SelectedField::Tags,
+            SelectedField::Tags => SelectedField::Desc,
             SelectedField::Desc => SelectedField::Title,
-            SelectedField::Status => SelectedField::Desc,
         };
     }
 
-    pub fn push_char(&mut self, new_char: char) {
+    // the description takes new lines and moves between them, everywhere else Enter and
+    // Up/Down go to another field
+    pub fn enter(&mut self) {
         match self.selected_field {
-            SelectedField::Title => self.title.push(new_char),
-            SelectedField::Desc => self.desc.push(new_char),
-            SelectedField::Status => (),
+            SelectedField::Desc => self.desc.push('\n'),
+            _ => self.prev_field(),
         }
     }
 
-    pub fn pop_char(&mut self) {
-        {
-            match self.selected_field {
-                SelectedField::Title => self.title.pop(),
-                SelectedField::Desc => self.desc.pop(),
-                SelectedField::Status => None,
-            };
+    pub fn up_arrow(&mut self) {
+        if self.selected_field != SelectedField::Desc || !self.desc.cursor_up() {
+            self.next_field();
         }
     }
 
-    pub fn delete_char(&mut self) {
+    pub fn down_arrow(&mut self) {
+        if self.selected_field != SelectedField::Desc || !self.desc.cursor_down() {
+            self.prev_field();
+        }
+    }
+
+    pub fn has_selection(&self) -> bool {
         match self.selected_field {
-            SelectedField::Title => self.title.delete_char(),
-            SelectedField::Desc => self.desc.delete_char(),
-            SelectedField::Status => (),
+            SelectedField::Title => self.title.has_selection(),
+            SelectedField::Due => self.due.has_selection(),
+            SelectedField::Scheduled => self.scheduled.has_selection(),
+            SelectedField::Tags => self.tags.has_selection(),
+            SelectedField::Desc => self.desc.has_selection(),
+            SelectedField::Status | SelectedField::Priority => false,
+        }
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        if let Some(input) = self.selected_input() {
+            input.paste(text);
         }
     }
 
-    pub fn right_arrow(&mut self) {
+    // text fields take the editing keys, status and priority cycle with Left/Right
+    pub fn handle_key(&mut self, key: &KeyEvent, clipboard: &mut String) {
+        if let Some(input) = self.selected_input() {
+            input.handle_key(key, clipboard);
+            return;
+        }
+        match key.code {
+            KeyCode::Right => self.right_arrow(),
+            KeyCode::Left => self.left_arrow(),
+            _ => (),
+        }
+    }
+
+    fn right_arrow(&mut self) {
         match self.selected_field {
-            SelectedField::Title => self.title.cursor_right(),
-            SelectedField::Desc => self.desc.cursor_right(),
-            SelectedField::Status => self.status.next_status(),
+            SelectedField::Status => self.status = self.statuses.next(self.status),
+            SelectedField::Priority => self.priority = self.priority.next(),
+            _ => (),
         }
     }
 
-    pub fn left_arrow(&mut self) {
+    fn left_arrow(&mut self) {
         match self.selected_field {
-            SelectedField::Title => self.title.cursor_left(),
-            SelectedField::Desc => self.desc.cursor_left(),
-            SelectedField::Status => self.status.prev_status(),
+            SelectedField::Status => self.status = self.statuses.prev(self.status),
+            SelectedField::Priority => self.priority = self.priority.prev(),
+            _ => (),
         }
     }
 }
 
-#[derive(Default)]
+#[derive(Default, PartialEq)]
 enum SelectedField {
     #[default]
     Title,
-    Desc,
     Status,
+    Priority,
+    Due,
+    Scheduled,
+    Tags,
+    Desc,
 }
@@ -0,0 +1,497 @@
+use crate::Task;
+use crate::TaskList;
+use crate::error::{DootError, Result};
+use crate::priority::Priority;
+use crate::settings::ParentStatus;
+use crate::state::Autosave;
+use crate::storage;
+use crate::tags;
+use crate::task_date::TaskDate;
+use crate::task_list::TaskIndexer;
+use crate::task_printer::TaskPrinter;
+use crate::task_status::{StatusColor, Statuses};
+use clap::{Arg, ArgAction, ArgMatches, Command, arg, command};
+
+pub fn command() -> Command {
+    command!()
+        .about("To do list in the terminal")
+        // only the terminal interface autosaves, so it's taken with no subcommand or with tui
+        .arg(autosave_arg())
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("add")
+                .about("Add a task, at the top level or under a parent task")
+                .arg(arg!(<TITLE> "Title of the new task"))
+                .arg(arg!(-d --desc <DESC> "Description of the new task"))
+                .arg(arg!(-p --parent <PATH> "Path of the parent task, e.g. 1.2"))
+                .arg(arg!(--due <DATE> "Due date, e.g. tomorrow, fri, +3d or 2026-11-01"))
+                .arg(arg!(--scheduled <DATE> "Day to start on, e.g. mon"))
+                .arg(
+                    arg!(--priority <PRIORITY> "none, low, med, high or urgent")
+                        .value_parser(Priority::parse),
+                )
+                .arg(
+                    arg!(--tag <TAG> "Tag the task, can be given more than once")
+                        .action(ArgAction::Append),
+                ),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("Print every task with its path")
+                .arg(arg!(-t --table "Print a table instead of a tree").action(ArgAction::SetTrue))
+                .arg(
+                    arg!(--plain "No colors or box drawing, for piping").action(ArgAction::SetTrue),
+                )
+                .arg(arg!(--due <DATE> "Only unfinished tasks due by then, e.g. today or fri"))
+                .arg(arg!(--tag <TAG> "Only tasks with this tag, e.g. backend or @alice")),
+        )
+        .subcommand(
+            Command::new("done")
+                .about("Mark a task as complete")
+                .arg(arg!(<PATH> "Path of the task, e.g. 1.2")),
+        )
+        .subcommand(
+            Command::new("set")
+                .about("Set the status of a task")
+                .arg(arg!(<PATH> "Path of the task, e.g. 1.2"))
+                .arg(arg!(<STATUS> "Name of the status")),
+        )
+        .subcommand(
+            Command::new("priority")
+                .about("Set the priority of a task")
+                .arg(arg!(<PATH> "Path of the task, e.g. 1.2"))
+                .arg(
+                    arg!(<PRIORITY> "none, low, med, high or urgent")
+                        .value_parser(Priority::parse),
+                ),
+        )
+        .subcommand(
+            Command::new("tag")
+                .about("Add tags to a task, words without a # or @ get a #")
+                .arg(arg!(<PATH> "Path of the task, e.g. 1.2"))
+                .arg(arg!(<TAGS>... "Tags to add, e.g. backend @alice")),
+        )
+        .subcommand(
+            Command::new("untag")
+                .about("Remove tags from a task")
+                .arg(arg!(<PATH> "Path of the task, e.g. 1.2"))
+                .arg(arg!(<TAGS>... "Tags to remove, e.g. backend @alice")),
+        )
+        .subcommand(
+            Command::new("due")
+                .about("Set or clear the due date of a task")
+                .arg(arg!(<PATH> "Path of the task, e.g. 1.2"))
+                .arg(arg!(<DATE> "e.g. tomorrow, fri, +3d, 2026-11-01 or none")),
+        )
+        .subcommand(
+            Command::new("schedule")
+                .about("Set or clear the day a task is meant to start")
+                .arg(arg!(<PATH> "Path of the task, e.g. 1.2"))
+                .arg(arg!(<DATE> "e.g. tomorrow, mon, +1w, 2026-11-01 or none")),
+        )
+        .subcommand(
+            Command::new("rm")
+                .about("Remove a task and all of its children")
+                .arg(arg!(<PATH> "Path of the task, e.g. 1.2")),
+        )
+        .subcommand(
+            Command::new("archive")
+                .about("Move finished tasks, along with their subtasks, into the archive")
+                .arg(arg!(-l --list "List the archived tasks, newest first").action(ArgAction::SetTrue))
+                .arg(
+                    arg!(--restore <N> "Put an archived task back where it was, 1 is the newest")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("restore")
+                .about("Roll the list back to one of its backups")
+                .arg(
+                    arg!([BACKUP] "Backup to restore, 1 is the most recent")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("1"),
+                )
+                .arg(arg!(-l --list "List the available backups").action(ArgAction::SetTrue)),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Show or change the settings saved with the list")
+                .arg(
+                    arg!(--"parent-status" <RULE> "Set parent statuses by hand or derive them from subtasks: manual or derived")
+                        .value_parser(ParentStatus::parse),
+                ),
+        )
+        .subcommand(
+            Command::new("status")
+                .about("List the statuses tasks can have, or change them")
+                .subcommand(
+                    Command::new("add")
+                        .about("Add a status, at the end of the cycle order unless a position is given")
+                        .arg(arg!(<NAME> "Name of the status"))
+                        .arg(
+                            arg!(-c --color <COLOR> "Color the status is drawn in")
+                                .value_parser(StatusColor::parse)
+                                .default_value("white"),
+                        )
+                        .arg(arg!(--done "Counts as done for progress").action(ArgAction::SetTrue))
+                        .arg(
+                            arg!(--position <N> "Place in the cycle order, 1 is the status new tasks start as")
+                                .value_parser(clap::value_parser!(usize)),
+                        ),
+                )
+                .subcommand(
+                    Command::new("edit")
+                        .about("Rename, recolor or move a status")
+                        .arg(arg!(<NAME> "Name of the status"))
+                        .arg(arg!(--rename <NEW_NAME> "New name for the status"))
+                        .arg(
+                            arg!(-c --color <COLOR> "Color the status is drawn in")
+                                .value_parser(StatusColor::parse),
+                        )
+                        .arg(
+                            arg!(--done <DONE> "Whether it counts as done: true or false")
+                                .value_parser(clap::value_parser!(bool)),
+                        )
+                        .arg(
+                            arg!(--position <N> "Place in the cycle order, 1 is the status new tasks start as")
+                                .value_parser(clap::value_parser!(usize)),
+                        ),
+                )
+                .subcommand(
+                    Command::new("rm")
+                        .about("Remove a status, tasks that had it go back to the first status")
+                        .arg(arg!(<NAME> "Name of the status")),
+                ),
+        )
+        .subcommand(
+            Command::new("tui")
+                .about("Open the terminal interface (default)")
+                .arg(autosave_arg()),
+        )
+}
+
+fn autosave_arg() -> Arg {
+    arg!(--autosave <WHEN> "Autosave in the terminal interface: off, change or idle seconds")
+        .value_parser(Autosave::parse)
+        .default_value("off")
+}
+
+// runs a subcommand against the task list, returns true when the list should be saved
+pub fn run_subcommand(tasks: &mut TaskList, name: &str, matches: &ArgMatches) -> Result<bool> {
+    let should_save = match name {
+        "add" => {
+            let title = matches.get_one::<String>("TITLE").unwrap().clone();
+            let desc = matches
+                .get_one::<String>("desc")
+                .cloned()
+                .unwrap_or_default();
+            let mut new_task = Task::new(title, tasks.statuses().initial(), desc);
+            new_task.set_due(date_from_arg(matches, "due")?);
+            new_task.set_scheduled(date_from_arg(matches, "scheduled")?);
+            if let Some(priority) = matches.get_one::<Priority>("priority") {
+                new_task.set_priority(*priority);
+            }
+            if let Some(new_tags) = matches.get_many::<String>("tag") {
+                new_task.add_tags(&tags::parse_tag_list(&tags_arg(new_tags)));
+            }
+            let mut parent = match matches.get_one::<String>("parent") {
+                Some(parent_path) => task_indexer_from_arg(tasks, parent_path)?,
+                None => TaskIndexer::Null,
+            };
+            tasks.add_child_task(new_task, &mut parent)?;
+            true
+        }
+        "list" => {
+            let mut printer = TaskPrinter::new(matches.get_flag("plain"));
+            if let Some(due_by) = date_from_arg(matches, "due")? {
+                let statuses = tasks.statuses().clone();
+                printer = printer.with_filter(move |task| task.is_due_by(due_by, &statuses));
+            }
+            if let Some(tag) = matches.get_one::<String>("tag").cloned() {
+                printer = printer.with_filter(move |task| task.has_tag(&tag));
+            }
+            let printed = match matches.get_flag("table") {
+                true => printer.print_table(tasks),
+                false => printer.print_tree(tasks),
+            };
+            // whatever is reading the list stopping early (doot list | head) isn't an error
+            if let Err(e) = printed
+                && e.kind() != std::io::ErrorKind::BrokenPipe
+            {
+                return Err(e.into());
+            }
+            false
+        }
+        "done" => {
+            let task_indexer =
+                task_indexer_from_arg(tasks, matches.get_one::<String>("PATH").unwrap())?;
+            let status = tasks.statuses().first_done();
+            if tasks.status_is_derived(&task_indexer) {
+                return Err(DootError::DerivedStatus);
+            }
+            tasks
+                .get_task_mut(&task_indexer)
+                .ok_or(DootError::InvalidSelection)?
+                .set_status(status);
+            true
+        }
+        "set" => {
+            let task_indexer =
+                task_indexer_from_arg(tasks, matches.get_one::<String>("PATH").unwrap())?;
+            let status = tasks
+                .statuses()
+                .find(matches.get_one::<String>("STATUS").unwrap())?;
+            if tasks.status_is_derived(&task_indexer) {
+                return Err(DootError::DerivedStatus);
+            }
+            tasks
+                .get_task_mut(&task_indexer)
+                .ok_or(DootError::InvalidSelection)?
+                .set_status(status);
+            true
+        }
+        "status" => match matches.subcommand() {
+            Some((name, matches)) => {
+                run_status_subcommand(tasks, name, matches)?;
+                true
+            }
+            None => {
+                print_statuses(tasks.statuses());
+                false
+            }
+        },
+        "priority" => {
+            let task_indexer =
+                task_indexer_from_arg(tasks, matches.get_one::<String>("PATH").unwrap())?;
+            tasks
+                .get_task_mut(&task_indexer)
+                .ok_or(DootError::InvalidSelection)?
+                .set_priority(*matches.get_one::<Priority>("PRIORITY").unwrap());
+            true
+        }
+        "tag" | "untag" => {
+            let task_indexer =
+                task_indexer_from_arg(tasks, matches.get_one::<String>("PATH").unwrap())?;
+            let given =
+                tags::parse_tag_list(&tags_arg(matches.get_many::<String>("TAGS").unwrap()));
+            let task = tasks
+                .get_task_mut(&task_indexer)
+                .ok_or(DootError::InvalidSelection)?;
+            match name {
+                "tag" => task.add_tags(&given),
+                _ => task.remove_tags(&given)?,
+            }
+            true
+        }
+        "due" | "schedule" => {
+            let task_indexer =
+                task_indexer_from_arg(tasks, matches.get_one::<String>("PATH").unwrap())?;
+            let date = date_from_arg(matches, "DATE")?;
+            let task = tasks
+                .get_task_mut(&task_indexer)
+                .ok_or(DootError::InvalidSelection)?;
+            match name {
+                "due" => task.set_due(date),
+                _ => task.set_scheduled(date),
+            }
+            true
+        }
+        "rm" => {
+            let mut task_indexer =
+                task_indexer_from_arg(tasks, matches.get_one::<String>("PATH").unwrap())?;
+            tasks.delete_task(&mut task_indexer)?;
+            true
+        }
+        "archive" => {
+            if matches.get_flag("list") {
+                print_archive(tasks);
+                return Ok(false);
+            }
+            match matches.get_one::<usize>("restore") {
+                Some(&number) => {
+                    let index = number
+                        .checked_sub(1)
+                        .and_then(|position| tasks.archive_index_from_newest(position))
+                        .ok_or(DootError::NoArchivedTask(number))?;
+                    let path = tasks
+                        .restore_path(index)
+                        .ok_or(DootError::NoArchivedTask(number))?;
+                    tasks.restore_at(index, &path)?;
+                    println!("restored to {}", format_task_path(&path));
+                }
+                None => {
+                    let paths = tasks.archive_finished(|_, _, _| ())?;
+                    println!("archived {} finished tasks", paths.len());
+                }
+            }
+            true
+        }
+        "config" => match matches.get_one::<ParentStatus>("parent-status") {
+            Some(parent_status) => {
+                tasks.set_parent_status(*parent_status);
+                true
+            }
+            None => {
+                println!("parent-status {}", tasks.settings().parent_status.name());
+                false
+            }
+        },
+        _ => false,
+    };
+    if should_save {
+        tasks.derive_statuses();
+        tasks.stamp_finished(Some(chrono::Local::now().timestamp()));
+    }
+    Ok(should_save)
+}
+
+// restore never loads the list itself, it's what's used when the list can't be read
+pub fn run_restore(file: &str, matches: &ArgMatches) -> Result<()> {
+    if matches.get_flag("list") {
+        print_backups(file);
+        return Ok(());
+    }
+    let backup = *matches.get_one::<usize>("BACKUP").unwrap();
+    let backup_path = storage::backup_path(file, backup);
+    let mut tasks =
+        storage::load_backup(file, backup).map_err(|e| DootError::Load(backup_path.clone(), e))?;
+    tasks.derive_statuses();
+    tasks.save(file, true)?;
+    println!("restored {backup_path}");
+    Ok(())
+}
+
+fn run_status_subcommand(tasks: &mut TaskList, name: &str, matches: &ArgMatches) -> Result<()> {
+    let mut status_name = matches.get_one::<String>("NAME").unwrap().clone();
+    match name {
+        "add" => tasks.statuses_mut().add(
+            &status_name,
+            *matches.get_one::<StatusColor>("color").unwrap(),
+            matches.get_flag("done"),
+        )?,
+        "edit" => {
+            if let Some(new_name) = matches.get_one::<String>("rename") {
+                tasks.statuses_mut().rename(&status_name, new_name)?;
+                status_name = new_name.clone();
+            }
+            let def = tasks.statuses_mut().get_mut(&status_name)?;
+            if let Some(color) = matches.get_one::<StatusColor>("color") {
+                def.color = *color;
+            }
+            if let Some(done) = matches.get_one::<bool>("done") {
+                def.done = *done;
+            }
+        }
+        "rm" => return tasks.remove_status(&status_name),
+        _ => (),
+    }
+    // positions are one based like task paths
+    if let Some(position) = matches.get_one::<usize>("position") {
+        tasks
+            .statuses_mut()
+            .reorder(&status_name, position.saturating_sub(1))?;
+    }
+    Ok(())
+}
+
+fn print_statuses(statuses: &Statuses) {
+    statuses.iter().enumerate().for_each(|(i, def)| {
+        let done = match def.done {
+            true => " (done)",
+            false => "",
+        };
+        println!(
+            "{} {} {}{done}",
+            i + 1,
+            statuses.colored_name(def.id),
+            def.color.name()
+        );
+    });
+}
+
+fn print_archive(tasks: &TaskList) {
+    if tasks.archive().is_empty() {
+        println!("nothing archived");
+    }
+    tasks
+        .archive()
+        .iter()
+        .rev()
+        .enumerate()
+        .for_each(|(i, entry)| {
+            println!(
+                "{} {} {} [{}] from {}",
+                i + 1,
+                entry.time_label(),
+                entry.task.title(),
+                tasks.statuses().colored_name(entry.task.status()),
+                entry.origin()
+            );
+        });
+}
+
+fn print_backups(file: &str) {
+    let backups = storage::list_backups(file);
+    if backups.is_empty() {
+        println!("no backups of {file}");
+    }
+    backups.iter().for_each(|info| {
+        let age = info
+            .modified
+            .and_then(|modified| modified.elapsed().ok())
+            .map(|elapsed| format_age(elapsed.as_secs()))
+            .unwrap_or_else(|| "unknown age".to_string());
+        let task_count = info
+            .task_count
+            .map(|count| format!("{count} tasks"))
+            .unwrap_or_else(|| "unreadable".to_string());
+        println!("{} {age} {task_count}", info.backup);
+    });
+}
+
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{seconds}s ago"),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+// paths are dotted and one based, "2.1" is the first child of the second task
+pub fn parse_task_path(path: &str) -> Option<Vec<usize>> {
+    path.split('.')
+        .map(|part| match part.trim().parse::<usize>() {
+            Ok(0) | Err(_) => None,
+            Ok(index) => Some(index - 1),
+        })
+        .collect()
+}
+
+fn tags_arg<'a>(values: impl Iterator<Item = &'a String>) -> String {
+    values.cloned().collect::<Vec<_>>().join(" ")
+}
+
+fn format_task_path(path: &[usize]) -> String {
+    path.iter()
+        .map(|index| (index + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn date_from_arg(matches: &ArgMatches, id: &str) -> Result<Option<TaskDate>> {
+    match matches.get_one::<String>(id) {
+        Some(date) => TaskDate::parse(date, TaskDate::today()),
+        None => Ok(None),
+    }
+}
+
+fn task_indexer_from_arg(tasks: &TaskList, path: &str) -> Result<TaskIndexer> {
+    let task_indexer = parse_task_path(path)
+        .map(|path| TaskIndexer::from_path(&path))
+        .unwrap_or(TaskIndexer::Null);
+    match tasks.get_task(&task_indexer) {
+        Some(_) => Ok(task_indexer),
+        None => Err(DootError::InvalidTaskPath(path.to_string())),
+    }
+}
@@ -0,0 +1,35 @@
+use crate::task::Task;
+use crate::task_status::Statuses;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::io::Write;
+
+// a task and its subtasks as a markdown checklist, for pasting outside of doot
+pub fn outline(task: &Task, statuses: &Statuses) -> String {
+    let mut lines = Vec::new();
+    push_outline(task, statuses, "", &mut lines);
+    lines.join("\n")
+}
+
+fn push_outline(task: &Task, statuses: &Statuses, indent: &str, lines: &mut Vec<String>) {
+    let mark = match statuses.is_done(task.status()) {
+        true => 'x',
+        false => ' ',
+    };
+    lines.push(format!("{indent}- [{mark}] {}", task.title()));
+    for line in task.desc().lines() {
+        lines.push(format!("{indent}  {line}"));
+    }
+    let child_indent = format!("{indent}  ");
+    for child in task.children() {
+        push_outline(child, statuses, &child_indent, lines);
+    }
+}
+
+// hands the text to the terminal's clipboard with an OSC 52 escape, which also works over ssh.
+// terminals that don't support it ignore the sequence
+pub fn export(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()
+}
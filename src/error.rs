@@ -0,0 +1,62 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, DootError>;
+
+#[derive(Debug)]
+pub enum DootError {
+    Io(std::io::Error),
+    Load(String, std::io::Error), // the file exists but couldn't be read as a task list
+    InvalidTaskPath(String),      // a path given on the command line doesn't point at a task
+    InvalidSelection,             // a TaskIndexer doesn't line up with the tasks it was used on
+    UnknownStatus(String),
+    DuplicateStatus(String),
+    LastStatus,      // a list needs at least one status for tasks to start as
+    TooManyStatuses, // status ids are a u8
+    InvalidDate(String),
+    TagInTitle(String), // tags written in a title go away by editing the title
+    NoArchivedTask(usize),
+    DerivedStatus, // the task has children and parent statuses come from them
+}
+
+impl fmt::Display for DootError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Load(file, e) => write!(f, "could not load {file}: {e}"),
+            Self::InvalidTaskPath(path) => write!(f, "no task at path {path}"),
+            Self::InvalidSelection => write!(f, "the selected task doesn't exist"),
+            Self::UnknownStatus(name) => write!(f, "no status named {name}"),
+            Self::DuplicateStatus(name) => write!(f, "there is already a status named {name}"),
+            Self::LastStatus => write!(f, "the last status can't be removed"),
+            Self::TooManyStatuses => write!(f, "a list can't have any more statuses"),
+            Self::InvalidDate(date) => write!(
+                f,
+                "can't read {date:?} as a date, try today, tomorrow, fri, +3d or 2026-11-01"
+            ),
+            Self::NoArchivedTask(number) => write!(
+                f,
+                "no archived task {number}, doot archive --list shows them"
+            ),
+            Self::TagInTitle(tag) => write!(f, "{tag} is written in the title, edit it out there"),
+            Self::DerivedStatus => write!(
+                f,
+                "this status follows the subtasks, doot config --parent-status manual sets parents by hand"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DootError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) | Self::Load(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DootError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -0,0 +1,506 @@
+use crate::Task;
+use crate::TaskList;
+use crate::archive::ArchivedTask;
+use crate::settings::ListSettings;
+use borsh::BorshDeserialize;
+use borsh::BorshSerialize;
+use std::io::{Error, ErrorKind, Read, Write};
+
+// every file starts with the magic bytes and a little endian u16 format version
+const MAGIC: &[u8; 4] = b"DOOT";
+pub const CURRENT_VERSION: u16 = 10;
+
+pub fn write_task_list<W: Write>(tasks: &TaskList, writer: &mut W) -> std::io::Result<()> {
+    writer.write_all(MAGIC)?;
+    CURRENT_VERSION.serialize(writer)?;
+    tasks.serialize(writer)
+}
+
+pub fn read_task_list<R: Read>(reader: &mut R) -> std::io::Result<TaskList> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+
+    let (version, body) = match bytes.strip_prefix(MAGIC) {
+        // files written before the header existed are the same layout as version 1
+        None => (1, bytes.as_slice()),
+        Some(mut body) => (u16::deserialize(&mut body)?, body),
+    };
+    let mut tasks = match version {
+        1 => decode_v1(body),
+        2 => decode_v2(body),
+        3 => decode_v3(body),
+        4 => decode_v4(body),
+        5 => decode_v5(body),
+        6 => decode_v6(body),
+        7 => decode_v7(body),
+        8 => decode_v8(body),
+        9 => decode_v9(body),
+        10 => decode_v10(body),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "file format version {version} is newer than this doot supports ({CURRENT_VERSION})"
+            ),
+        )),
+    }?;
+    // nothing says when tasks that were already done in older files were finished
+    if version < CURRENT_VERSION {
+        tasks.stamp_finished(None);
+    }
+    Ok(tasks)
+}
+
+fn decode_v1(body: &[u8]) -> std::io::Result<TaskList> {
+    let tasks = Vec::<v1::Task>::try_from_slice(body)?;
+    Ok(TaskList::from_tasks(
+        tasks.into_iter().map(v1::Task::upgrade).collect(),
+    ))
+}
+
+// version 2 had no list settings after the tasks
+fn decode_v2(body: &[u8]) -> std::io::Result<TaskList> {
+    let tasks = Vec::<v2::Task>::try_from_slice(body)?;
+    Ok(TaskList::from_tasks(upgrade_v2(tasks)))
+}
+
+// version 3 settings had the parent status rule but no status definitions
+fn decode_v3(body: &[u8]) -> std::io::Result<TaskList> {
+    let (tasks, settings) = <(Vec<v2::Task>, v3::ListSettings)>::try_from_slice(body)?;
+    Ok(TaskList::new(upgrade_v2(tasks), settings.upgrade()))
+}
+
+fn decode_v4(body: &[u8]) -> std::io::Result<TaskList> {
+    let (tasks, settings) = <(Vec<v2::Task>, v4::ListSettings)>::try_from_slice(body)?;
+    Ok(TaskList::new(upgrade_v2(tasks), settings.upgrade()))
+}
+
+fn decode_v5(body: &[u8]) -> std::io::Result<TaskList> {
+    let (tasks, settings) = <(Vec<v5::Task>, v4::ListSettings)>::try_from_slice(body)?;
+    Ok(TaskList::new(
+        tasks.into_iter().map(v5::Task::upgrade).collect(),
+        settings.upgrade(),
+    ))
+}
+
+fn decode_v6(body: &[u8]) -> std::io::Result<TaskList> {
+    let (tasks, settings) = <(Vec<v6::Task>, v4::ListSettings)>::try_from_slice(body)?;
+    Ok(TaskList::new(
+        tasks.into_iter().map(v6::Task::upgrade).collect(),
+        settings.upgrade(),
+    ))
+}
+
+// version 7 had no archive after the settings
+fn decode_v7(body: &[u8]) -> std::io::Result<TaskList> {
+    let (tasks, settings) = <(Vec<v7::Task>, v4::ListSettings)>::try_from_slice(body)?;
+    Ok(TaskList::new(upgrade_v7(tasks), settings.upgrade()))
+}
+
+fn decode_v8(body: &[u8]) -> std::io::Result<TaskList> {
+    let (tasks, settings, archive) =
+        <(Vec<v7::Task>, v4::ListSettings, Vec<v7::ArchivedTask>)>::try_from_slice(body)?;
+    Ok(TaskList::new(upgrade_v7(tasks), settings.upgrade()).with_archive(upgrade_archive(archive)))
+}
+
+// version 9 had no finish times on its tasks
+fn decode_v9(body: &[u8]) -> std::io::Result<TaskList> {
+    let (tasks, settings, archive) =
+        <(Vec<v7::Task>, ListSettings, Vec<v7::ArchivedTask>)>::try_from_slice(body)?;
+    Ok(TaskList::new(upgrade_v7(tasks), settings).with_archive(upgrade_archive(archive)))
+}
+
+fn decode_v10(body: &[u8]) -> std::io::Result<TaskList> {
+    TaskList::try_from_slice(body)
+}
+
+fn upgrade_v2(tasks: Vec<v2::Task>) -> Vec<Task> {
+    tasks.into_iter().map(v2::Task::upgrade).collect()
+}
+
+fn upgrade_v7(tasks: Vec<v7::Task>) -> Vec<Task> {
+    tasks.into_iter().map(v7::Task::upgrade).collect()
+}
+
+fn upgrade_archive(archive: Vec<v7::ArchivedTask>) -> Vec<ArchivedTask> {
+    archive.into_iter().map(v7::ArchivedTask::upgrade).collect()
+}
+
+// layouts of older versions, each one upgrades straight to the current Task
+mod v1 {
+    use crate::task_status::TaskStatus;
+    use borsh::BorshDeserialize;
+
+    #[derive(BorshDeserialize)]
+    pub struct Task {
+        title: String,
+        status: TaskStatus,
+        desc: String,
+        children: Vec<Task>,
+    }
+
+    impl Task {
+        pub fn upgrade(self) -> super::Task {
+            let mut task = super::Task::new(self.title, self.status, self.desc);
+            self.children
+                .into_iter()
+                .for_each(|child| task.add_child(child.upgrade()));
+            task
+        }
+    }
+}
+
+// tasks from version 2 through 4, before due and scheduled dates
+mod v2 {
+    use crate::task_status::TaskStatus;
+    use borsh::BorshDeserialize;
+
+    #[derive(BorshDeserialize)]
+    pub struct Task {
+        title: String,
+        status: TaskStatus,
+        desc: String,
+        children: Vec<Task>,
+        collapsed: bool,
+    }
+
+    impl Task {
+        pub fn upgrade(self) -> super::Task {
+            let mut task = super::Task::new(self.title, self.status, self.desc);
+            task.set_collapsed(self.collapsed);
+            self.children
+                .into_iter()
+                .for_each(|child| task.add_child(child.upgrade()));
+            task
+        }
+    }
+}
+
+mod v3 {
+    use crate::settings::ParentStatus;
+    use borsh::BorshDeserialize;
+
+    #[derive(BorshDeserialize)]
+    pub struct ListSettings {
+        parent_status: ParentStatus,
+    }
+
+    impl ListSettings {
+        pub fn upgrade(self) -> crate::settings::ListSettings {
+            crate::settings::ListSettings {
+                parent_status: self.parent_status,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+// settings from version 4 through 8, before status ids stopped being reused
+mod v4 {
+    use crate::settings::ParentStatus;
+    use crate::task_status::{StatusDef, Statuses};
+    use borsh::BorshDeserialize;
+
+    #[derive(BorshDeserialize)]
+    pub struct ListSettings {
+        parent_status: ParentStatus,
+        statuses: Vec<StatusDef>,
+    }
+
+    impl ListSettings {
+        pub fn upgrade(self) -> crate::settings::ListSettings {
+            crate::settings::ListSettings {
+                parent_status: self.parent_status,
+                statuses: Statuses::from_defs(self.statuses),
+            }
+        }
+    }
+}
+
+// tasks from version 5, before priorities
+mod v5 {
+    use crate::task_date::TaskDate;
+    use crate::task_status::TaskStatus;
+    use borsh::BorshDeserialize;
+
+    #[derive(BorshDeserialize)]
+    pub struct Task {
+        title: String,
+        status: TaskStatus,
+        desc: String,
+        children: Vec<Task>,
+        collapsed: bool,
+        due: Option<TaskDate>,
+        scheduled: Option<TaskDate>,
+    }
+
+    impl Task {
+        pub fn upgrade(self) -> super::Task {
+            let mut task = super::Task::new(self.title, self.status, self.desc);
+            task.set_collapsed(self.collapsed);
+            task.set_due(self.due);
+            task.set_scheduled(self.scheduled);
+            self.children
+                .into_iter()
+                .for_each(|child| task.add_child(child.upgrade()));
+            task
+        }
+    }
+}
+
+// tasks from version 6, before tags, the tags written in their titles are picked up
+mod v6 {
+    use crate::priority::Priority;
+    use crate::tags;
+    use crate::task_date::TaskDate;
+    use crate::task_status::TaskStatus;
+    use borsh::BorshDeserialize;
+
+    #[derive(BorshDeserialize)]
+    pub struct Task {
+        title: String,
+        status: TaskStatus,
+        desc: String,
+        children: Vec<Task>,
+        collapsed: bool,
+        due: Option<TaskDate>,
+        scheduled: Option<TaskDate>,
+        priority: Priority,
+    }
+
+    impl Task {
+        pub fn upgrade(self) -> super::Task {
+            let mut task = super::Task::new(self.title, self.status, self.desc);
+            task.set_collapsed(self.collapsed);
+            task.set_due(self.due);
+            task.set_scheduled(self.scheduled);
+            task.set_priority(self.priority);
+            task.set_tags(tags::tags_in(task.title()));
+            self.children
+                .into_iter()
+                .for_each(|child| task.add_child(child.upgrade()));
+            task
+        }
+    }
+}
+
+// tasks from version 7 through 9, before finish times
+mod v7 {
+    use crate::priority::Priority;
+    use crate::task_date::TaskDate;
+    use crate::task_status::TaskStatus;
+    use borsh::BorshDeserialize;
+
+    #[derive(BorshDeserialize)]
+    pub struct Task {
+        title: String,
+        status: TaskStatus,
+        desc: String,
+        children: Vec<Task>,
+        collapsed: bool,
+        due: Option<TaskDate>,
+        scheduled: Option<TaskDate>,
+        priority: Priority,
+        tags: Vec<String>,
+    }
+
+    impl Task {
+        pub fn upgrade(self) -> super::Task {
+            let mut task = super::Task::new(self.title, self.status, self.desc);
+            task.set_collapsed(self.collapsed);
+            task.set_due(self.due);
+            task.set_scheduled(self.scheduled);
+            task.set_priority(self.priority);
+            task.set_tags(self.tags);
+            self.children
+                .into_iter()
+                .for_each(|child| task.add_child(child.upgrade()));
+            task
+        }
+    }
+
+    #[derive(BorshDeserialize)]
+    pub struct ArchivedTask {
+        task: Task,
+        path: Vec<usize>,
+        parent_titles: Vec<String>,
+        archived_at: i64,
+    }
+
+    impl ArchivedTask {
+        pub fn upgrade(self) -> crate::archive::ArchivedTask {
+            crate::archive::ArchivedTask {
+                task: self.task.upgrade(),
+                path: self.path,
+                parent_titles: self.parent_titles,
+                archived_at: self.archived_at,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::priority::Priority;
+    use crate::settings::ParentStatus;
+    use crate::task::Finished;
+    use crate::task_date::TaskDate;
+    use crate::task_status::{StatusColor, Statuses};
+
+    fn date(input: &str) -> TaskDate {
+        TaskDate::parse(input, TaskDate::today()).unwrap().unwrap()
+    }
+
+    // a parent with a finished child, using whatever fields the version had
+    fn tasks_as_of(version: u16) -> Vec<Task> {
+        let statuses = Statuses::default();
+        let mut parent = Task::new(
+            "Plan #work".to_string(),
+            statuses.initial(),
+            "notes\nmore notes".to_string(),
+        );
+        parent.add_child(Task::new(
+            "Step".to_string(),
+            statuses.first_done(),
+            String::new(),
+        ));
+        if version >= 2 {
+            parent.set_collapsed(true);
+        }
+        if version >= 5 {
+            parent.set_due(Some(date("2026-11-01")));
+            parent.set_scheduled(Some(date("2026-10-20")));
+        }
+        if version >= 6 {
+            parent.set_priority(Priority::High);
+        }
+        if version >= 7 {
+            parent.add_tags(&["@alice".to_string()]);
+        }
+        vec![parent]
+    }
+
+    fn settings_as_of(version: u16) -> ListSettings {
+        let mut settings = ListSettings::default();
+        if version >= 3 {
+            settings.parent_status = ParentStatus::Derived;
+        }
+        if version >= 4 {
+            settings
+                .statuses
+                .add("Waiting", StatusColor::Yellow, false)
+                .unwrap();
+        }
+        settings
+    }
+
+    // the fields of the old task layouts in the order they were written
+    fn write_legacy_task(task: &Task, version: u16, out: &mut Vec<u8>) {
+        task.title().serialize(out).unwrap();
+        task.status().serialize(out).unwrap();
+        task.desc().serialize(out).unwrap();
+        (task.children().len() as u32).serialize(out).unwrap();
+        task.children()
+            .iter()
+            .for_each(|child| write_legacy_task(child, version, out));
+        if version >= 2 {
+            task.is_collapsed().serialize(out).unwrap();
+        }
+        if version >= 5 {
+            task.due().serialize(out).unwrap();
+            task.scheduled().serialize(out).unwrap();
+        }
+        if version >= 6 {
+            task.priority().serialize(out).unwrap();
+        }
+        if version >= 7 {
+            task.tags().serialize(out).unwrap();
+        }
+    }
+
+    // settings before the next status id was saved
+    fn write_legacy_settings(settings: &ListSettings, out: &mut Vec<u8>) {
+        settings.parent_status.serialize(out).unwrap();
+        let defs: Vec<_> = settings.statuses.iter().collect();
+        defs.serialize(out).unwrap();
+    }
+
+    fn legacy_file(version: u16) -> Vec<u8> {
+        let tasks = tasks_as_of(version);
+        let settings = settings_as_of(version);
+        let mut out = MAGIC.to_vec();
+        version.serialize(&mut out).unwrap();
+        (tasks.len() as u32).serialize(&mut out).unwrap();
+        tasks
+            .iter()
+            .for_each(|task| write_legacy_task(task, version, &mut out));
+        match version {
+            3 => settings.parent_status.serialize(&mut out).unwrap(),
+            4..=8 => write_legacy_settings(&settings, &mut out),
+            9.. => settings.serialize(&mut out).unwrap(),
+            _ => (),
+        }
+        if version >= 8 {
+            Vec::<ArchivedTask>::new().serialize(&mut out).unwrap();
+        }
+        out
+    }
+
+    fn assert_same(decoded: &TaskList, expected: &TaskList) {
+        assert_eq!(
+            borsh::to_vec(decoded).unwrap(),
+            borsh::to_vec(expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn every_old_version_decodes_into_the_current_list() {
+        for version in 1..CURRENT_VERSION {
+            let decoded = read_task_list(&mut legacy_file(version).as_slice())
+                .unwrap_or_else(|e| panic!("version {version}: {e}"));
+            let mut expected = TaskList::new(tasks_as_of(version), settings_as_of(version));
+            expected.stamp_finished(None);
+            assert_same(&decoded, &expected);
+            assert!(decoded.tasks()[0].children()[0].finished() == Finished::Unknown);
+        }
+    }
+
+    #[test]
+    fn files_without_a_header_are_read_as_version_1() {
+        let file = legacy_file(1);
+        let body = &file[MAGIC.len() + 2..];
+        let decoded = read_task_list(&mut &body[..]).unwrap();
+        let mut expected = TaskList::from_tasks(tasks_as_of(1));
+        expected.stamp_finished(None);
+        assert_same(&decoded, &expected);
+    }
+
+    #[test]
+    fn the_current_version_round_trips_with_its_archive() {
+        let mut tasks = tasks_as_of(CURRENT_VERSION);
+        tasks.push(Task::new(
+            "Old".to_string(),
+            Statuses::default().first_done(),
+            String::new(),
+        ));
+        let mut list = TaskList::new(tasks, settings_as_of(CURRENT_VERSION));
+        list.stamp_finished(Some(1_792_000_000));
+        let entry = list.archive_entry(&[1]).unwrap();
+        list.archive_at(&[1], 0, entry).unwrap();
+
+        let mut file = vec![];
+        write_task_list(&list, &mut file).unwrap();
+        let decoded = read_task_list(&mut file.as_slice()).unwrap();
+        assert_same(&decoded, &list);
+        assert_eq!(decoded.archive().len(), 1);
+        assert!(decoded.archive()[0].task.finished() == Finished::At(1_792_000_000));
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let mut file = MAGIC.to_vec();
+        (CURRENT_VERSION + 1).serialize(&mut file).unwrap();
+        let result = read_task_list(&mut file.as_slice());
+        assert!(result.is_err_and(|e| e.kind() == ErrorKind::InvalidData));
+    }
+}
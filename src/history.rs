@@ -0,0 +1,402 @@
+use crate::Task;
+use crate::TaskList;
+use crate::archive::ArchivedTask;
+use crate::error::Result;
+use crate::settings::ParentStatus;
+use crate::task_list::TaskIndexer;
+use std::collections::VecDeque;
+
+// oldest edits are dropped once the history is this long
+const HISTORY_LIMIT: usize = 100;
+
+// a change to the list that can be applied in either direction, tasks are found by their
+// path of child indices so edits stay valid as long as they're undone in order
+#[derive(Clone)]
+pub enum Edit {
+    Insert {
+        path: Vec<usize>,
+        task: Task,
+    },
+    Remove {
+        path: Vec<usize>,
+        task: Task,
+    },
+    Update {
+        path: Vec<usize>,
+        before: Task,
+        after: Task,
+    },
+    Move {
+        from: Vec<usize>,
+        to: Vec<usize>,
+    },
+    Archive {
+        path: Vec<usize>,
+        index: usize, // position in the archive
+        entry: ArchivedTask,
+    },
+    Restore {
+        path: Vec<usize>,
+        index: usize,
+        entry: ArchivedTask,
+    },
+    // the statuses it overwrites are recorded separately as updates
+    ParentStatus {
+        path: Vec<usize>, // the selection when it was switched
+        before: ParentStatus,
+        after: ParentStatus,
+    },
+    // several edits undone and redone together, applied in order
+    Batch(Vec<Edit>),
+}
+
+impl Edit {
+    // applies the edit and returns the path that should be selected afterwards
+    fn apply(&self, tasks: &mut TaskList) -> Result<Vec<usize>> {
+        match self {
+            Self::Insert { path, task } => {
+                tasks.insert_at(path, task.clone())?;
+                Ok(path.clone())
+            }
+            Self::Remove { path, .. } => {
+                tasks.remove_at(path)?;
+                Ok(path.clone())
+            }
+            Self::Update { path, after, .. } => {
+                tasks.edit_task(after.clone(), &TaskIndexer::from_path(path))?;
+                Ok(path.clone())
+            }
+            Self::Move { from, to } => {
+                tasks.move_path(from, to)?;
+                Ok(to.clone())
+            }
+            Self::Archive { path, index, entry } => {
+                tasks.archive_at(path, *index, entry.clone())?;
+                Ok(path.clone())
+            }
+            Self::Restore { path, index, .. } => {
+                tasks.restore_at(*index, path)?;
+                Ok(path.clone())
+            }
+            Self::ParentStatus { path, after, .. } => {
+                tasks.set_parent_status(*after);
+                Ok(path.clone())
+            }
+            Self::Batch(edits) => {
+                let mut selected_path = vec![];
+                for edit in edits {
+                    selected_path = edit.apply(tasks)?;
+                }
+                Ok(selected_path)
+            }
+        }
+    }
+
+    fn inverse(&self) -> Self {
+        match self {
+            Self::Insert { path, task } => Self::Remove {
+                path: path.clone(),
+                task: task.clone(),
+            },
+            Self::Remove { path, task } => Self::Insert {
+                path: path.clone(),
+                task: task.clone(),
+            },
+            Self::Update {
+                path,
+                before,
+                after,
+            } => Self::Update {
+                path: path.clone(),
+                before: after.clone(),
+                after: before.clone(),
+            },
+            Self::Move { from, to } => Self::Move {
+                from: to.clone(),
+                to: from.clone(),
+            },
+            Self::Archive { path, index, entry } => Self::Restore {
+                path: path.clone(),
+                index: *index,
+                entry: entry.clone(),
+            },
+            Self::Restore { path, index, entry } => Self::Archive {
+                path: path.clone(),
+                index: *index,
+                entry: entry.clone(),
+            },
+            Self::ParentStatus {
+                path,
+                before,
+                after,
+            } => Self::ParentStatus {
+                path: path.clone(),
+                before: *after,
+                after: *before,
+            },
+            Self::Batch(edits) => Self::Batch(edits.iter().rev().map(Self::inverse).collect()),
+        }
+    }
+
+    // an update for every task whose status differs between two copies of the same tasks
+    pub fn status_updates(before: &[Task], after: &[Task]) -> Vec<Edit> {
+        let mut edits = vec![];
+        push_status_updates(before, after, &mut vec![], &mut edits);
+        edits
+    }
+}
+
+fn push_status_updates(
+    before: &[Task],
+    after: &[Task],
+    path: &mut Vec<usize>,
+    edits: &mut Vec<Edit>,
+) {
+    for (index, (before, after)) in before.iter().zip(after).enumerate() {
+        path.push(index);
+        if before.status() != after.status() {
+            edits.push(Edit::Update {
+                path: path.clone(),
+                before: before.clone(),
+                after: after.clone(),
+            });
+        }
+        push_status_updates(before.children(), after.children(), path, edits);
+        path.pop();
+    }
+}
+
+#[derive(Default)]
+pub struct History {
+    undo_stack: VecDeque<Edit>,
+    redo_stack: Vec<Edit>,
+}
+
+impl History {
+    pub fn record(&mut self, edit: Edit) {
+        self.redo_stack.clear();
+        self.undo_stack.push_back(edit);
+        if self.undo_stack.len() > HISTORY_LIMIT {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    // Ok(None) means there was nothing to undo
+    pub fn undo(&mut self, tasks: &mut TaskList) -> Result<Option<Vec<usize>>> {
+        let Some(edit) = self.undo_stack.pop_back() else {
+            return Ok(None);
+        };
+        let selected_path = edit.inverse().apply(tasks)?;
+        self.redo_stack.push(edit);
+        Ok(Some(selected_path))
+    }
+
+    pub fn redo(&mut self, tasks: &mut TaskList) -> Result<Option<Vec<usize>>> {
+        let Some(edit) = self.redo_stack.pop() else {
+            return Ok(None);
+        };
+        let selected_path = edit.apply(tasks)?;
+        self.undo_stack.push_back(edit);
+        Ok(Some(selected_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task_status::Statuses;
+
+    fn task(title: &str) -> Task {
+        Task::new(
+            title.to_string(),
+            Statuses::default().initial(),
+            String::new(),
+        )
+    }
+
+    // three tasks, the first with a subtask
+    fn list() -> TaskList {
+        let mut first = task("first");
+        first.add_child(task("child"));
+        TaskList::from_tasks(vec![first, task("second"), task("third")])
+    }
+
+    fn bytes(tasks: &TaskList) -> Vec<u8> {
+        borsh::to_vec(tasks).unwrap()
+    }
+
+    // applying the edit changes the list, its inverse puts it back and it can be applied again
+    fn assert_reversible(mut tasks: TaskList, edit: Edit) {
+        let before = bytes(&tasks);
+        edit.apply(&mut tasks).unwrap();
+        let after = bytes(&tasks);
+        assert_ne!(before, after);
+        edit.inverse().apply(&mut tasks).unwrap();
+        assert_eq!(bytes(&tasks), before);
+        edit.apply(&mut tasks).unwrap();
+        assert_eq!(bytes(&tasks), after);
+    }
+
+    #[test]
+    fn insert_and_remove_are_reversible() {
+        assert_reversible(
+            list(),
+            Edit::Insert {
+                path: vec![1],
+                task: task("new"),
+            },
+        );
+        assert_reversible(
+            list(),
+            Edit::Remove {
+                path: vec![0, 0],
+                task: task("child"),
+            },
+        );
+    }
+
+    #[test]
+    fn update_is_reversible() {
+        let mut after = task("renamed");
+        after.set_status(Statuses::default().first_done());
+        assert_reversible(
+            list(),
+            Edit::Update {
+                path: vec![2],
+                before: task("third"),
+                after,
+            },
+        );
+    }
+
+    #[test]
+    fn move_is_reversible() {
+        assert_reversible(
+            list(),
+            Edit::Move {
+                from: vec![2],
+                to: vec![0, 1],
+            },
+        );
+    }
+
+    #[test]
+    fn archive_and_restore_are_reversible() {
+        let tasks = list();
+        let entry = tasks.archive_entry(&[0, 0]).unwrap();
+        assert_reversible(
+            tasks,
+            Edit::Archive {
+                path: vec![0, 0],
+                index: 0,
+                entry: entry.clone(),
+            },
+        );
+
+        let mut archived = list();
+        archived.archive_at(&[0, 0], 0, entry.clone()).unwrap();
+        assert_reversible(
+            archived,
+            Edit::Restore {
+                path: vec![0, 0],
+                index: 0,
+                entry,
+            },
+        );
+    }
+
+    #[test]
+    fn batch_is_reversible() {
+        let tasks = list();
+        let third = tasks.archive_entry(&[2]).unwrap();
+        let second = tasks.archive_entry(&[1]).unwrap();
+        assert_reversible(
+            tasks,
+            Edit::Batch(vec![
+                Edit::Archive {
+                    path: vec![2],
+                    index: 0,
+                    entry: third,
+                },
+                Edit::Archive {
+                    path: vec![1],
+                    index: 1,
+                    entry: second,
+                },
+            ]),
+        );
+    }
+
+    // the first task's only child is done while the first task isn't
+    fn list_with_a_done_child() -> TaskList {
+        let mut tasks = list();
+        let mut child = task("child");
+        child.set_status(Statuses::default().first_done());
+        tasks
+            .edit_task(child, &TaskIndexer::from_path(&[0, 0]))
+            .unwrap();
+        tasks
+    }
+
+    #[test]
+    fn deriving_parent_statuses_is_reversible() {
+        let tasks = list_with_a_done_child();
+        let mut derived = list_with_a_done_child();
+        derived.set_parent_status(ParentStatus::Derived);
+        derived.derive_statuses();
+        let mut edits = vec![Edit::ParentStatus {
+            path: vec![0],
+            before: ParentStatus::Manual,
+            after: ParentStatus::Derived,
+        }];
+        edits.extend(Edit::status_updates(tasks.tasks(), derived.tasks()));
+        assert_eq!(edits.len(), 2);
+
+        let edit = Edit::Batch(edits);
+        let mut applied = list_with_a_done_child();
+        edit.apply(&mut applied).unwrap();
+        assert_eq!(bytes(&applied), bytes(&derived));
+        assert_reversible(tasks, edit);
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_history() {
+        let mut tasks = list();
+        let mut history = History::default();
+        let original = bytes(&tasks);
+        let edit = Edit::Insert {
+            path: vec![3],
+            task: task("fourth"),
+        };
+        edit.apply(&mut tasks).unwrap();
+        history.record(edit);
+        let changed = bytes(&tasks);
+
+        assert_eq!(history.undo(&mut tasks).unwrap(), Some(vec![3]));
+        assert_eq!(bytes(&tasks), original);
+        assert_eq!(history.undo(&mut tasks).unwrap(), None);
+        assert_eq!(history.redo(&mut tasks).unwrap(), Some(vec![3]));
+        assert_eq!(bytes(&tasks), changed);
+        assert_eq!(history.redo(&mut tasks).unwrap(), None);
+    }
+
+    #[test]
+    fn only_the_last_edits_are_kept() {
+        let mut tasks = TaskList::default();
+        let mut history = History::default();
+        for i in 0..HISTORY_LIMIT + 1 {
+            let edit = Edit::Insert {
+                path: vec![i],
+                task: task(&i.to_string()),
+            };
+            edit.apply(&mut tasks).unwrap();
+            history.record(edit);
+        }
+        for _ in 0..HISTORY_LIMIT {
+            assert!(history.undo(&mut tasks).unwrap().is_some());
+        }
+        assert_eq!(history.undo(&mut tasks).unwrap(), None);
+        // the oldest edit was dropped so its task is still there
+        assert_eq!(tasks.task_count(), 1);
+    }
+}
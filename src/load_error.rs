@@ -0,0 +1,130 @@
+use crate::TaskList;
+use crate::error::DootError;
+use crate::storage;
+use crate::storage::BackupInfo;
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Paragraph, Wrap};
+
+// shown in place of the task list when the file can't be loaded, the broken file is left
+// alone and gets rotated into the backups the next time the list is saved
+pub struct LoadErrorScreen {
+    file: String,
+    error: DootError,
+    backups: Vec<BackupInfo>,
+    selected_backup: usize,
+}
+
+impl LoadErrorScreen {
+    pub fn new(file: &str, error: DootError) -> Self {
+        Self {
+            file: file.to_string(),
+            error,
+            backups: storage::list_backups(file),
+            selected_backup: 0,
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let app_block = Block::bordered()
+            .title(Line::from(" Could not load the task list ").red().bold())
+            .title_bottom(
+                Line::from(vec![
+                    " Choose Backup ".into(),
+                    "<Up>/<Down>".green().bold(),
+                    " Open Backup ".into(),
+                    "<Enter>".green().bold(),
+                    " Start Fresh ".into(),
+                    "<n>".green().bold(),
+                    " Quit ".into(),
+                    "<Esc/q> ".green().bold(),
+                ])
+                .centered(),
+            );
+
+        let paintable_area = app_block.inner(frame.area());
+        frame.render_widget(app_block, frame.area());
+
+        let [error_area, backups_area] =
+            Layout::vertical([Constraint::Length(4), Constraint::Fill(1)]).areas(paintable_area);
+
+        frame.render_widget(
+            Paragraph::new(self.error.to_string())
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(&*self.file)),
+            error_area,
+        );
+
+        let mut lines: Vec<Line> = self
+            .backups
+            .iter()
+            .enumerate()
+            .map(|(i, info)| {
+                let task_count = match info.task_count {
+                    Some(count) => format!("{count} tasks"),
+                    None => "unreadable".to_string(),
+                };
+                let line = Line::from(format!(
+                    " {} {task_count} ",
+                    storage::backup_path(&self.file, info.backup)
+                ));
+                match i == self.selected_backup {
+                    true => line.cyan().bold(),
+                    false => line,
+                }
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::from(
+                " no backups found, press <n> to start a new list ",
+            ));
+        }
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("Backups")),
+            backups_area,
+        );
+    }
+
+    pub fn run<B: Backend>(mut self, terminal: &mut Terminal<B>) -> Option<TaskList> {
+        loop {
+            let _ = terminal.draw(|frame| self.draw(frame));
+
+            let Ok(event) = event::read() else {
+                break None;
+            };
+            match event {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('q') | KeyCode::Esc,
+                    ..
+                }) => break None,
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('n'),
+                    ..
+                }) => break Some(TaskList::default()),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Up, ..
+                }) => self.selected_backup = self.selected_backup.saturating_sub(1),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Down,
+                    ..
+                }) if self.selected_backup + 1 < self.backups.len() => self.selected_backup += 1,
+                Event::Key(KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                }) => {
+                    let Some(info) = self.backups.get(self.selected_backup) else {
+                        continue;
+                    };
+                    match storage::load_backup(&self.file, info.backup) {
+                        Ok(tasks) => break Some(tasks),
+                        Err(e) => {
+                            self.error =
+                                DootError::Load(storage::backup_path(&self.file, info.backup), e)
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
@@ -1,49 +1,177 @@
 use borsh::BorshDeserialize;
 use crossterm::event::KeyModifiers;
+mod archive;
 mod buffered_task;
+mod cli;
+mod clipboard;
+mod error;
+mod file_format;
+mod history;
+mod load_error;
+mod priority;
+mod search;
+mod settings;
 mod state;
+mod storage;
+mod tags;
 mod task;
+mod task_date;
 mod task_list;
+mod task_printer;
 mod task_status;
 mod text_input;
-use crate::task_list::TaskList;
+mod view;
+mod viewport;
+mod wrap;
+use crate::task_list::{Movement, TaskList};
 use crossterm::event;
 use crossterm::event::Event;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
-use state::State;
+use crossterm::event::{DisableBracketedPaste, EnableBracketedPaste};
+use error::{DootError, Result};
+use load_error::LoadErrorScreen;
+use ratatui::DefaultTerminal;
+use state::{Autosave, State};
 use task::Task;
 
+const DOOT_FILE: &str = ".doot";
+
 fn main() {
-    let tasks = match std::fs::File::open(".doot") {
-        Ok(mut file) => match TaskList::try_from_reader(&mut file) {
-            Ok(tasks) => tasks,
-            Err(e) => panic!("{e:?}"),
-        },
-        Err(_) => match std::fs::write(".doot", TaskList::default_bytes()) {
-            Ok(_new_file) => TaskList::default(),
-            Err(e) => panic!("{e:?}"),
-        },
+    let matches = cli::command().get_matches();
+    let result = match matches.subcommand() {
+        Some(("tui", tui_matches)) => {
+            let autosave = *tui_matches.get_one::<Autosave>("autosave").unwrap();
+            run_terminal_interface(DOOT_FILE, autosave)
+        }
+        None => {
+            let autosave = *matches.get_one::<Autosave>("autosave").unwrap();
+            run_terminal_interface(DOOT_FILE, autosave)
+        }
+        Some((name, sub_matches)) => run_command(DOOT_FILE, name, sub_matches),
     };
-    load_terminal_interface(tasks);
+    if let Err(e) = result {
+        eprintln!("doot: {e}");
+        std::process::exit(1);
+    }
 }
 
-fn load_terminal_interface(tasks: TaskList) {
-    let mut terminal = ratatui::init();
-    let selected_task = tasks.first_task();
-    let mut application = State {
-        tasks,
-        selected_task,
+fn run_command(file: &str, name: &str, matches: &clap::ArgMatches) -> Result<()> {
+    if name == "restore" {
+        return cli::run_restore(file, matches);
+    }
+    let mut tasks = load_tasks(file)?;
+    if cli::run_subcommand(&mut tasks, name, matches)? {
+        tasks.save(file, true)?;
+    }
+    Ok(())
+}
+
+fn load_tasks(file: &str) -> Result<TaskList> {
+    match std::fs::File::open(file) {
+        Ok(mut open_file) => {
+            TaskList::load(&mut open_file).map_err(|e| DootError::Load(file.to_string(), e))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            std::fs::write(file, TaskList::default_bytes())?;
+            Ok(TaskList::default())
+        }
+        Err(e) => Err(DootError::Load(file.to_string(), e)),
+    }
+}
+
+fn init_terminal() -> Result<DefaultTerminal> {
+    let terminal = ratatui::try_init()?;
+    // pasted text arrives as one event instead of a key press per character
+    crossterm::execute!(std::io::stdout(), EnableBracketedPaste)?;
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        hook(info);
+    }));
+    Ok(terminal)
+}
+
+fn restore_terminal() {
+    let _ = crossterm::execute!(std::io::stdout(), DisableBracketedPaste);
+    ratatui::restore();
+}
+
+fn run_terminal_interface(file: &str, autosave: Autosave) -> Result<()> {
+    let mut terminal = init_terminal()?;
+    let tasks = match load_tasks(file) {
+        Ok(tasks) => Some(tasks),
+        Err(e) => LoadErrorScreen::new(file, e).run(&mut terminal),
     };
+    if let Some(tasks) = tasks {
+        terminal_interface_loop(&mut terminal, State::new(tasks, file, autosave));
+    }
+    restore_terminal();
+    Ok(())
+}
 
+fn terminal_interface_loop(terminal: &mut DefaultTerminal, mut application: State) {
     loop {
         let _ = terminal.draw(|f| application.draw(f));
 
-        match event::read().unwrap() {
+        if let Some(timeout) = application.autosave_timeout() {
+            match event::poll(timeout) {
+                Ok(true) => (),
+                Ok(false) => {
+                    application.autosave_if_idle();
+                    continue;
+                }
+                Err(_) => break,
+            }
+        }
+
+        let Ok(event) = event::read() else {
+            break;
+        };
+        if let Event::Key(_) = event {
+            application.clear_message();
+        }
+        match event {
             Event::Key(KeyEvent {
                 code: KeyCode::Char('q') | KeyCode::Esc,
                 ..
-            }) => break,
+            }) if application.confirm_quit_loop(terminal) => break,
+            Event::Key(KeyEvent {
+                code: KeyCode::Up,
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            })
+            | Event::Key(KeyEvent {
+                code: KeyCode::Char('K'),
+                ..
+            }) => application.move_task(Movement::Up),
+            Event::Key(KeyEvent {
+                code: KeyCode::Down,
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            })
+            | Event::Key(KeyEvent {
+                code: KeyCode::Char('J'),
+                ..
+            }) => application.move_task(Movement::Down),
+            Event::Key(KeyEvent {
+                code: KeyCode::Right,
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            })
+            | Event::Key(KeyEvent {
+                code: KeyCode::Char('>'),
+                ..
+            }) => application.move_task(Movement::Indent),
+            Event::Key(KeyEvent {
+                code: KeyCode::Left,
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            })
+            | Event::Key(KeyEvent {
+                code: KeyCode::Char('<'),
+                ..
+            }) => application.move_task(Movement::Outdent),
             Event::Key(KeyEvent {
                 code: KeyCode::Up | KeyCode::BackTab,
                 ..
@@ -64,30 +192,112 @@ fn load_terminal_interface(tasks: TaskList) {
                 code: KeyCode::Char('d'),
                 ..
             }) => application.delete_task(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('D'),
+                ..
+            }) => application.archive_finished(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('V'),
+                ..
+            }) => application.archive_loop(terminal),
             Event::Key(KeyEvent {
                 code: KeyCode::Char('s'),
                 modifiers: KeyModifiers::CONTROL,
                 ..
-            }) => break application.save(".doot"),
-            // Event::Key(KeyEvent {
-            // code: KeyCode::Char('e'),
-            // ..
-            // }) => application.edit_task_loop(&mut terminal),
+            }) => {
+                let _ = application.save();
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('s'),
+                ..
+            }) => application.cycle_sort(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('t'),
+                ..
+            }) => application.filter_tag_loop(terminal),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('f'),
+                ..
+            }) => application.cycle_status_filter(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('h'),
+                ..
+            }) => application.toggle_hide_done(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('/'),
+                ..
+            }) => application.search_loop(terminal),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('n'),
+                ..
+            }) => application.next_match(true),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('N'),
+                ..
+            }) => application.next_match(false),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('e'),
+                ..
+            }) => application.edit_task_loop(terminal),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => application.toggle_collapsed(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('C'),
+                ..
+            }) => application.collapse_all(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(depth @ '1'..='9'),
+                ..
+            }) => application.expand_to_depth(depth as usize - '0' as usize),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(' '),
+                ..
+            }) => application.next_status(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('x'),
+                ..
+            }) => application.toggle_complete(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('P'),
+                ..
+            }) => application.toggle_parent_status(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('y'),
+                ..
+            }) => application.yank(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('p'),
+                ..
+            }) => application.put(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('u'),
+                ..
+            }) => application.undo(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('r'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            }) => application.redo(),
             Event::Key(KeyEvent {
                 /*
                 a -> append
                 A -> prepend
                 I -> insert above
                 i -> insert below
+                o -> add as the last child of the selected task
                 */
                 code:
                     task_add_kind @ (KeyCode::Char('a')
                     | KeyCode::Char('A')
                     | KeyCode::Char('i')
-                    | KeyCode::Char('I')),
+                    | KeyCode::Char('I')
+                    | KeyCode::Char('o')),
                 ..
             }) => {
-                if let Some(new_task) = application.add_task_loop(&mut terminal) {
+                if let Some(new_task) = application.add_task_loop(terminal) {
                     application.handle_new_task(new_task, task_add_kind);
                 }
             }
@@ -95,5 +305,4 @@ fn load_terminal_interface(tasks: TaskList) {
             _ => (),
         }
     }
-    ratatui::restore();
 }
@@ -0,0 +1,74 @@
+use borsh::BorshDeserialize;
+use borsh::BorshSerialize;
+use ratatui::prelude::*;
+
+// ordered from least to most pressing so sorting by priority can compare them directly
+#[derive(
+    Default, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Med,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    const ALL: [Self; 5] = [Self::None, Self::Low, Self::Med, Self::High, Self::Urgent];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Low => "low",
+            Self::Med => "med",
+            Self::High => "high",
+            Self::Urgent => "urgent",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|priority| priority.name() == value.to_lowercase())
+            .ok_or_else(|| format!("expected none, low, med, high or urgent, got {value}"))
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        Self::ALL[(self as usize + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::None => Color::DarkGray,
+            Self::Low => Color::Blue,
+            Self::Med => Color::Yellow,
+            Self::High => Color::LightRed,
+            Self::Urgent => Color::Red,
+        }
+    }
+
+    // shown in front of the title, tasks without a priority don't get one
+    pub fn badge(&self) -> Option<Span<'static>> {
+        match self {
+            Self::None => None,
+            _ => Some(
+                Span::from(format!(" {} ", self.name().to_uppercase()))
+                    .style(Style::new().bg(self.color()).fg(Color::Black).bold()),
+            ),
+        }
+    }
+
+    pub fn to_line(self, selected: bool) -> Line<'static> {
+        let line = Line::from(self.name()).style(self.color());
+        match selected {
+            true => line.bold(),
+            false => line,
+        }
+    }
+}
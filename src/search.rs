@@ -0,0 +1,121 @@
+use ratatui::prelude::*;
+
+// byte ranges of text that match query, ignoring case and not overlapping
+fn match_ranges(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let query: Vec<char> = query.chars().collect();
+    let mut ranges = vec![];
+    if query.is_empty() {
+        return ranges;
+    }
+    let mut next_free = 0;
+    for (start, _) in text.char_indices() {
+        if start < next_free {
+            continue;
+        }
+        let mut end = start;
+        let mut chars = text[start..].chars();
+        let found = query.iter().all(|query_char| match chars.next() {
+            Some(c) if c.to_lowercase().eq(query_char.to_lowercase()) => {
+                end += c.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        if found {
+            ranges.push((start, end));
+            next_free = end;
+        }
+    }
+    ranges
+}
+
+pub fn matches(text: &str, query: &str) -> bool {
+    !match_ranges(text, query).is_empty()
+}
+
+// splits text into spans with the parts matching query picked out
+pub fn highlight<'a>(text: &'a str, query: Option<&str>) -> Vec<Span<'a>> {
+    let ranges = query.map_or(vec![], |query| match_ranges(text, query));
+    let mut spans = vec![];
+    let mut last = 0;
+    ranges.into_iter().for_each(|(start, end)| {
+        if start > last {
+            spans.push(Span::from(&text[last..start]));
+        }
+        spans.push(Span::from(&text[start..end]).black().on_yellow());
+        last = end;
+    });
+    if last < text.len() || spans.is_empty() {
+        spans.push(Span::from(&text[last..]));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matched<'a>(text: &'a str, query: &str) -> Vec<&'a str> {
+        match_ranges(text, query)
+            .into_iter()
+            .map(|(start, end)| &text[start..end])
+            .collect()
+    }
+
+    #[test]
+    fn matches_ignore_case() {
+        assert_eq!(
+            matched("Fix the LOGIN page, login works", "login"),
+            ["LOGIN", "login"]
+        );
+        assert!(matches("Backend", "KEND"));
+        assert!(!matches("Backend", "front"));
+    }
+
+    #[test]
+    fn an_empty_query_matches_nothing() {
+        assert!(match_ranges("anything", "").is_empty());
+        assert!(!matches("", ""));
+    }
+
+    #[test]
+    fn matches_do_not_overlap() {
+        assert_eq!(match_ranges("aaaa", "aa"), [(0, 2), (2, 4)]);
+        assert_eq!(match_ranges("aaa", "aa"), [(0, 2)]);
+    }
+
+    #[test]
+    fn ranges_are_bytes_around_multi_byte_text() {
+        assert_eq!(match_ranges("café Café", "café"), [(0, 5), (6, 11)]);
+        assert_eq!(matched("日本語のテキスト", "テキ"), ["テキ"]);
+        assert_eq!(matched("ÜBER über", "über"), ["ÜBER", "über"]);
+    }
+
+    #[test]
+    fn a_query_longer_than_the_rest_of_the_text_is_no_match() {
+        assert!(match_ranges("ab", "abc").is_empty());
+        assert!(match_ranges("é", "éé").is_empty());
+    }
+
+    #[test]
+    fn highlight_splits_around_matches() {
+        let spans = highlight("a test of tests", Some("test"));
+        let texts: Vec<&str> = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(texts, ["a ", "test", " of ", "test", "s"]);
+        assert_eq!(spans[1].style.bg, Some(Color::Yellow));
+        assert_eq!(spans[0].style.bg, None);
+    }
+
+    #[test]
+    fn highlight_without_a_match_is_the_whole_text() {
+        let texts = |query| {
+            highlight("plain", query)
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(None), ["plain"]);
+        assert_eq!(texts(Some("zzz")), ["plain"]);
+        assert_eq!(highlight("", None).len(), 1);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::task_status::Statuses;
+use borsh::BorshDeserialize;
+use borsh::BorshSerialize;
+
+// options that belong to a task list rather than to one task, saved in the same file
+#[derive(Default, BorshSerialize, BorshDeserialize, Clone)]
+pub struct ListSettings {
+    pub parent_status: ParentStatus,
+    pub statuses: Statuses,
+}
+
+// whether a task with children gets its status by hand or from how far along the children are
+#[derive(Default, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq)]
+pub enum ParentStatus {
+    #[default]
+    Manual,
+    Derived,
+}
+
+impl ParentStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Derived => "derived",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "manual" => Ok(Self::Manual),
+            "derived" => Ok(Self::Derived),
+            _ => Err(format!("expected manual or derived, got {value}")),
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Manual => Self::Derived,
+            Self::Derived => Self::Manual,
+        }
+    }
+}
@@ -1,29 +1,288 @@
 use crate::Task;
+use crate::archive::ArchiveBrowser;
 use crate::buffered_task::BufferedTask;
+use crate::clipboard;
 use crossterm::event::KeyModifiers;
 
 use crate::TaskList;
-use crate::task_list::TaskIndexer;
+use crate::error::Result;
+use crate::history::{Edit, History};
+use crate::settings::ParentStatus;
+use crate::task_list::{Movement, TaskIndexer};
+use crate::task_status::TaskStatus;
+use crate::text_input::TextInputField;
+use crate::view::{SortMode, StatusFilter, View};
+use crate::viewport::Viewport;
 use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use ratatui::prelude::*;
-use ratatui::widgets::Block;
+use ratatui::widgets::{Block, Clear, Paragraph};
+use std::time::{Duration, Instant};
+
+const DERIVED_STATUS_MESSAGE: &str = "this status follows the subtasks, <P> sets parents by hand";
+
+#[derive(Clone, Copy)]
+pub enum Autosave {
+    Off,
+    OnChange,
+    Idle(Duration), // save once nothing has changed for this long
+}
+
+impl Autosave {
+    // "off", "change" or a number of idle seconds, used as the clap value parser
+    pub fn parse(value: &str) -> std::result::Result<Self, String> {
+        match value {
+            "off" => Ok(Self::Off),
+            "change" => Ok(Self::OnChange),
+            seconds => match seconds.parse::<u64>() {
+                Ok(0) => Ok(Self::OnChange),
+                Ok(seconds) => Ok(Self::Idle(Duration::from_secs(seconds))),
+                Err(_) => Err(format!(
+                    "expected off, change or a number of seconds, got {value}"
+                )),
+            },
+        }
+    }
+}
 
 pub struct State {
     pub tasks: TaskList,
     pub selected_task: TaskIndexer,
+    pub message: Option<String>,
+    file: String,
+    dirty: bool,
+    backed_up: bool, // the list as it was before this session has been rotated into the backups
+    autosave: Autosave,
+    last_change: Instant,
+    history: History,
+    viewport: Viewport,
+    view: View,
+    search: Option<String>, // the last search, highlighted in the list and stepped through with n
+    clipboard: String,      // text cut, copied or killed in any input field
+    register: Option<Task>, // the last yanked task along with its subtasks
 }
 
 impl State {
+    pub fn new(tasks: TaskList, file: &str, autosave: Autosave) -> Self {
+        let selected_task = tasks.first_task();
+        Self {
+            tasks,
+            selected_task,
+            message: None,
+            file: file.to_string(),
+            dirty: false,
+            backed_up: false,
+            autosave,
+            last_change: Instant::now(),
+            history: History::default(),
+            viewport: Viewport::default(),
+            view: View::default(),
+            search: None,
+            clipboard: String::new(),
+            register: None,
+        }
+    }
+
+    // like handle_result but for operations that change the list, successful edits are
+    // recorded so they can be undone
+    fn handle_mutation(&mut self, result: Result<()>, edit: Edit) {
+        if result.is_ok() {
+            self.history.record(edit);
+            self.mark_changed();
+        }
+        self.handle_result(result);
+    }
+
+    fn mark_changed(&mut self) {
+        self.tasks.derive_statuses();
+        self.tasks
+            .stamp_finished(Some(chrono::Local::now().timestamp()));
+        self.mark_view_changed();
+        if let Autosave::OnChange = self.autosave {
+            let _ = self.save();
+        }
+    }
+
+    // collapsing and expanding is saved with the list but isn't worth a save of its own, it
+    // goes out with the next change or idle save
+    fn mark_view_changed(&mut self) {
+        self.dirty = true;
+        self.last_change = Instant::now();
+    }
+
+    pub fn undo(&mut self) {
+        match self.history.undo(&mut self.tasks) {
+            Ok(Some(path)) => {
+                self.select_nearest(&path);
+                self.mark_changed();
+            }
+            Ok(None) => self.message = Some("nothing to undo".to_string()),
+            Err(e) => self.handle_result(Err(e)),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        match self.history.redo(&mut self.tasks) {
+            Ok(Some(path)) => {
+                self.select_nearest(&path);
+                self.mark_changed();
+            }
+            Ok(None) => self.message = Some("nothing to redo".to_string()),
+            Err(e) => self.handle_result(Err(e)),
+        }
+    }
+
+    // selects the task at path, falling back to the one above it and then its parent
+    // when the task at path was just removed
+    fn select_nearest(&mut self, path: &[usize]) {
+        let mut path = path.to_vec();
+        while !path.is_empty() {
+            let task_indexer = TaskIndexer::from_path(&path);
+            if self.tasks.get_task(&task_indexer).is_some() {
+                self.tasks.expand_ancestors(&path);
+                self.selected_task = task_indexer;
+                return;
+            }
+            if let Some(last @ 1..) = path.pop() {
+                path.push(last - 1);
+            }
+        }
+        self.selected_task = self.tasks.first_task();
+    }
+
+    // where a task added with one of a/A/i/I ends up
+    fn new_task_path(&self, task_add_kind: KeyCode) -> Vec<usize> {
+        let mut path = self.selected_task.path();
+        if let (KeyCode::Char('o'), Some(selected_task)) =
+            (task_add_kind, self.tasks.get_task(&self.selected_task))
+        {
+            path.push(selected_task.child_count());
+            return path;
+        }
+        let Some(selected_index) = path.pop() else {
+            return vec![0];
+        };
+        let new_index = match task_add_kind {
+            KeyCode::Char('A') => 0,
+            KeyCode::Char('I') => selected_index,
+            KeyCode::Char('i') => selected_index + 1,
+            _ => self.tasks.sibling_count(&path),
+        };
+        path.push(new_index);
+        path
+    }
+
+    // how long the event loop can wait before an idle autosave is due
+    pub fn autosave_timeout(&self) -> Option<Duration> {
+        match self.autosave {
+            Autosave::Idle(idle) if self.dirty => {
+                Some(idle.saturating_sub(self.last_change.elapsed()))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn autosave_if_idle(&mut self) {
+        if let Some(Duration::ZERO) = self.autosave_timeout() {
+            let _ = self.save();
+        }
+    }
+
+    // errors from the task list mean the selection is out of step with it, so the selection
+    // is reset and the error is shown in the title bar instead of bringing the app down
+    fn handle_result(&mut self, result: Result<()>) {
+        if let Err(e) = result {
+            self.selected_task = self.tasks.first_task();
+            self.message = Some(e.to_string());
+        }
+    }
+
+    pub fn clear_message(&mut self) {
+        self.message = None;
+    }
+
     pub fn incr_level(&mut self) {
-        self.tasks.incr_level(&mut self.selected_task);
+        // moving into a collapsed task opens it up first
+        if let Some(task) = self.tasks.get_task_mut(&self.selected_task)
+            && task.is_collapsed()
+            && task.child_count() != 0
+        {
+            task.set_collapsed(false);
+            self.mark_view_changed();
+        }
+        if !self.view.is_plain() {
+            self.tasks.step_in(&mut self.selected_task, &self.view);
+            return;
+        }
+        let result = self.tasks.incr_level(&mut self.selected_task);
+        self.handle_result(result);
+    }
+
+    pub fn move_task(&mut self, movement: Movement) {
+        // moving by the stored order while it's hidden would look like nothing happened
+        if self.view.sort != SortMode::Stored {
+            self.message = Some(format!(
+                "sorted by {}, press s until stored order to move tasks",
+                self.view.sort.name()
+            ));
+            return;
+        }
+        if let Some(tag) = &self.view.tag {
+            self.message = Some(format!(
+                "filtered by {tag}, press t and clear the tag to move tasks"
+            ));
+            return;
+        }
+        if self.view.status != StatusFilter::All {
+            self.message = Some(format!(
+                "{}, press f until all statuses to move tasks",
+                self.view.status.name()
+            ));
+            return;
+        }
+        let from = self.selected_task.path();
+        let result = self.tasks.move_task(&mut self.selected_task, movement);
+        let to = self.selected_task.path();
+        if from != to {
+            self.handle_mutation(result, Edit::Move { from, to });
+        } else {
+            self.handle_result(result);
+        }
+    }
+
+    pub fn toggle_collapsed(&mut self) {
+        if let Some(task) = self.tasks.get_task_mut(&self.selected_task)
+            && task.child_count() != 0
+        {
+            task.set_collapsed(!task.is_collapsed());
+            self.mark_view_changed();
+        }
+    }
+
+    pub fn collapse_all(&mut self) {
+        self.expand_to_depth(1);
+    }
+
+    pub fn expand_to_depth(&mut self, depth: usize) {
+        self.tasks.expand_to_depth(depth);
+        // the selection can't stay inside a task that was just collapsed
+        let path = self.selected_task.path();
+        if path.len() > depth {
+            self.selected_task = TaskIndexer::from_path(&path[..depth]);
+        }
+        self.mark_view_changed();
     }
 
     pub fn decr_level(&mut self) {
-        self.tasks.decr_level(&mut self.selected_task);
+        let result = self.tasks.decr_level(&mut self.selected_task);
+        self.handle_result(result);
     }
 
     pub fn handle_new_task(&mut self, new_task: Task, task_add_kind: KeyCode) {
-        match task_add_kind {
+        let edit = Edit::Insert {
+            path: self.new_task_path(task_add_kind),
+            task: new_task.clone(),
+        };
+        let result = match task_add_kind {
             KeyCode::Char('a') => self.tasks.append_task(new_task, &mut self.selected_task),
             KeyCode::Char('A') => self.tasks.prepend_task(new_task, &mut self.selected_task),
             KeyCode::Char('I') => self
@@ -32,32 +291,281 @@ impl State {
             KeyCode::Char('i') => self
                 .tasks
                 .insert_task_below(new_task, &mut self.selected_task),
-            _ => (),
+            KeyCode::Char('o') => self.tasks.add_child_task(new_task, &mut self.selected_task),
+            _ => return,
+        };
+        self.handle_mutation(result, edit);
+    }
+
+    // copies the selected task and its subtasks to be put back with p, and to the terminal's
+    // clipboard as a checklist
+    pub fn yank(&mut self) {
+        let Some(task) = self.tasks.get_task(&self.selected_task) else {
+            return;
+        };
+        let _ = clipboard::export(&clipboard::outline(task, self.tasks.statuses()));
+        self.message = Some(match task.count() {
+            1 => format!("yanked \"{}\"", task.title()),
+            2 => format!("yanked \"{}\" and 1 subtask", task.title()),
+            count => format!("yanked \"{}\" and {} subtasks", task.title(), count - 1),
+        });
+        self.register = Some(task.clone());
+    }
+
+    // puts a copy of the yanked task below the selected one
+    pub fn put(&mut self) {
+        match self.register.clone() {
+            Some(task) => self.handle_new_task(task, KeyCode::Char('i')),
+            None => self.message = Some("nothing yanked".to_string()),
+        }
+    }
+
+    // changes the selected task in place and records the change so it can be undone
+    fn update_selected_task(&mut self, update: impl FnOnce(&mut Task)) {
+        let Some(before) = self.tasks.get_task(&self.selected_task).cloned() else {
+            return;
+        };
+        let mut after = before.clone();
+        update(&mut after);
+        let edit = Edit::Update {
+            path: self.selected_task.path(),
+            before,
+            after: after.clone(),
+        };
+        let result = self.tasks.edit_task(after, &self.selected_task);
+        self.handle_mutation(result, edit);
+    }
+
+    // a derived parent status would be put straight back, so the change is refused rather
+    // than leaving an undo step that does nothing
+    fn set_selected_status(&mut self, status: TaskStatus) {
+        if self.tasks.status_is_derived(&self.selected_task) {
+            self.message = Some(DERIVED_STATUS_MESSAGE.to_string());
+            return;
+        }
+        self.update_selected_task(|task| task.set_status(status));
+    }
+
+    pub fn next_status(&mut self) {
+        let Some(task) = self.tasks.get_task(&self.selected_task) else {
+            return;
         };
+        let status = self.tasks.statuses().next(task.status());
+        self.set_selected_status(status);
+    }
+
+    // marks the selected task done, or back to the initial status if it already was
+    pub fn toggle_complete(&mut self) {
+        let Some(task) = self.tasks.get_task(&self.selected_task) else {
+            return;
+        };
+        let statuses = self.tasks.statuses();
+        let status = match statuses.is_done(task.status()) {
+            true => statuses.initial(),
+            false => statuses.first_done(),
+        };
+        self.set_selected_status(status);
+    }
+
+    // switches between setting parent statuses by hand and deriving them from the children,
+    // the hand set statuses that deriving overwrites come back with an undo
+    pub fn toggle_parent_status(&mut self) {
+        let before = self.tasks.tasks().to_vec();
+        let parent_status = self.tasks.settings().parent_status;
+        self.tasks.set_parent_status(parent_status.toggled());
+        self.tasks.derive_statuses();
+        let mut edits = vec![Edit::ParentStatus {
+            path: self.selected_task.path(),
+            before: parent_status,
+            after: parent_status.toggled(),
+        }];
+        edits.extend(Edit::status_updates(&before, self.tasks.tasks()));
+        self.history.record(Edit::Batch(edits));
+        self.mark_changed();
     }
 
     pub fn delete_task(&mut self) {
-        self.tasks.delete_task(&mut self.selected_task);
+        let Some(task) = self.tasks.get_task(&self.selected_task).cloned() else {
+            return;
+        };
+        let edit = Edit::Remove {
+            path: self.selected_task.path(),
+            task,
+        };
+        let result = self.tasks.delete_task(&mut self.selected_task);
+        self.handle_mutation(result, edit);
+    }
+
+    // moves every finished task, subtasks and all, out of the list and into the archive
+    pub fn archive_finished(&mut self) {
+        let Some(first_path) = self.tasks.finished_paths().first().cloned() else {
+            self.message = Some("no finished tasks to archive".to_string());
+            return;
+        };
+        let mut edits = vec![];
+        let result = self.tasks.archive_finished(|path, index, entry| {
+            edits.push(Edit::Archive {
+                path: path.to_vec(),
+                index,
+                entry: entry.clone(),
+            })
+        });
+        let count = edits.len();
+        if !edits.is_empty() {
+            self.history.record(Edit::Batch(edits));
+            self.mark_changed();
+            self.select_nearest(&first_path);
+            self.message = Some(match count {
+                1 => "archived 1 task, <V> shows the archive".to_string(),
+                _ => format!("archived {count} tasks, <V> shows the archive"),
+            });
+        }
+        self.handle_result(result.map(|_| ()));
+    }
+
+    pub fn archive_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) {
+        let mut browser = ArchiveBrowser::default();
+        loop {
+            let _ = terminal
+                .draw(|frame| browser.draw(frame, self.tasks.archive(), self.tasks.statuses()));
+
+            let Ok(event) = event::read() else {
+                break;
+            };
+            match event {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('q') | KeyCode::Esc,
+                    ..
+                }) => break,
+                Event::Key(KeyEvent {
+                    code: KeyCode::Up, ..
+                }) => browser.selected = browser.selected.saturating_sub(1),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Down,
+                    ..
+                }) if browser.selected + 1 < self.tasks.archive().len() => browser.selected += 1,
+                Event::Key(KeyEvent {
+                    code: KeyCode::Enter | KeyCode::Char('r'),
+                    ..
+                }) => {
+                    let Some(index) = self.tasks.archive_index_from_newest(browser.selected) else {
+                        continue;
+                    };
+                    self.restore_archived(index);
+                    break;
+                }
+                _ => (),
+            }
+        }
     }
 
-    pub fn save(self, file: &str) {
-        let Self { tasks, .. } = self;
-        tasks.save(file);
+    fn restore_archived(&mut self, index: usize) {
+        let (Some(path), Some(entry)) = (
+            self.tasks.restore_path(index),
+            self.tasks.archive().get(index).cloned(),
+        ) else {
+            return;
+        };
+        let result = self.tasks.restore_at(index, &path);
+        if result.is_ok() {
+            self.selected_task = TaskIndexer::from_path(&path);
+        }
+        self.handle_mutation(result, Edit::Restore { path, index, entry });
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        let result = self.tasks.save(&self.file, !self.backed_up);
+        match &result {
+            Ok(()) => {
+                self.dirty = false;
+                self.backed_up = true;
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+        result
     }
 
-    pub fn draw(&self, frame: &mut Frame) {
-        let app_block = Block::bordered().title_bottom(
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let mut app_block = Block::bordered();
+        if let Some(message) = &self.message {
+            app_block = app_block.title(Line::from(format!(" {message} ")).red().bold());
+        }
+        let save_status = match (self.dirty, self.autosave) {
+            (false, _) => Line::from(" saved ").green(),
+            (true, Autosave::Off) => Line::from(" unsaved <ctrl+s> ").yellow(),
+            (true, _) => Line::from(" unsaved, autosave on ").yellow(),
+        };
+        if self.tasks.settings().parent_status == ParentStatus::Derived {
+            app_block = app_block.title(
+                Line::from(" parent status from subtasks ")
+                    .dark_gray()
+                    .right_aligned(),
+            );
+        }
+        if self.view.sort != SortMode::Stored {
+            app_block = app_block.title(
+                Line::from(format!(" sorted by {} ", self.view.sort.name()))
+                    .dark_gray()
+                    .right_aligned(),
+            );
+        }
+        if let Some(query) = &self.search {
+            app_block = app_block.title(
+                Line::from(format!(" search {query:?} "))
+                    .yellow()
+                    .right_aligned(),
+            );
+        }
+        if let Some(tag) = &self.view.tag {
+            app_block =
+                app_block.title(Line::from(format!(" tag {tag} ")).magenta().right_aligned());
+        }
+        if self.view.status != StatusFilter::All {
+            app_block = app_block.title(
+                Line::from(format!(" {} ", self.view.status.name()))
+                    .dark_gray()
+                    .right_aligned(),
+            );
+        }
+        app_block = app_block.title(save_status.right_aligned());
+        let app_block = app_block.title_bottom(
             Line::from(vec![
                 " Change Task ".into(),
                 "<Up>/<Down>".green().bold(),
                 " Change Level ".into(),
                 "<Left>/<Right>".green().bold(),
+                " Move Task ".into(),
+                "<shift+arrows>".green().bold(),
+                " Save ".into(),
+                "<ctrl+s>".green().bold(),
                 " Quit ".into(),
                 "<Esc/q>".green().bold(),
-                " Add Task (Start/End/Above/Below) ".into(),
-                "<A/a/I/i>".green().bold(),
+                " Add Task (Start/End/Above/Below/Subtask) ".into(),
+                "<A/a/I/i/o>".green().bold(),
+                " Edit Task ".into(),
+                "<e>".green().bold(),
+                " Next Status/Complete ".into(),
+                "<space>/<x>".green().bold(),
                 " Delete Task ".into(),
-                "<d> ".green().bold(),
+                "<d>".green().bold(),
+                " Yank/Put Task ".into(),
+                "<y>/<p>".green().bold(),
+                " Archive Finished/Show Archive ".into(),
+                "<D>/<V>".green().bold(),
+                " Undo/Redo ".into(),
+                "<u>/<ctrl+r>".green().bold(),
+                " Collapse (One/All/To Depth) ".into(),
+                "<c/C/1-9>".green().bold(),
+                " Sort ".into(),
+                "<s>".green().bold(),
+                " Filter Tag ".into(),
+                "<t>".green().bold(),
+                " Filter Status/Hide Done ".into(),
+                "<f>/<h>".green().bold(),
+                " Search/Next/Prev ".into(),
+                "</>/<n>/<N>".green().bold(),
+                " Parent Status Manual/Derived ".into(),
+                "<P> ".green().bold(),
             ])
             .centered(),
         );
@@ -66,64 +574,337 @@ impl State {
 
         frame.render_widget(app_block, frame.area());
 
-        self.tasks.draw(frame, paintable_area, &self.selected_task);
+        // the selected task can drop out of view when the filter changes or it's edited
+        if let Some(selection) = self
+            .tasks
+            .visible_selection(&self.selected_task, &self.view)
+        {
+            self.selected_task = selection;
+        }
+        let rows = self.tasks.rows(&self.selected_task, &self.view);
+        self.viewport.draw(
+            frame,
+            paintable_area,
+            &rows,
+            self.tasks.statuses(),
+            self.search.as_deref(),
+        );
     }
 
     pub fn incr(&mut self) {
-        self.tasks.incr(&mut self.selected_task);
+        if !self.view.is_plain() {
+            self.tasks.step(&mut self.selected_task, &self.view, false);
+            return;
+        }
+        let result = self.tasks.incr(&mut self.selected_task);
+        self.handle_result(result);
     }
 
     pub fn decr(&mut self) {
-        self.tasks.decr(&mut self.selected_task);
+        if !self.view.is_plain() {
+            self.tasks.step(&mut self.selected_task, &self.view, true);
+            return;
+        }
+        let result = self.tasks.decr(&mut self.selected_task);
+        self.handle_result(result);
     }
 
-    pub fn add_task_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Option<Task> {
-        let mut buffered_task = BufferedTask::default();
+    pub fn cycle_sort(&mut self) {
+        self.view.sort = self.view.sort.next();
+    }
+
+    pub fn cycle_status_filter(&mut self) {
+        self.view.status = self.view.status.next();
+    }
+
+    // the filter wanted most often gets its own key, pressing it again shows everything
+    pub fn toggle_hide_done(&mut self) {
+        self.view.status = match self.view.status {
+            StatusFilter::HideDone => StatusFilter::All,
+            _ => StatusFilter::HideDone,
+        };
+    }
+
+    // asks for a tag to filter by, an empty tag shows everything again
+    pub fn filter_tag_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) {
+        let known_tags = self.tasks.all_tags().join(" ");
+        let current = self.view.tag.clone().unwrap_or_default();
+        if let Some(tag) = self.prompt_loop(terminal, " Filter by tag ", &current, |_, _| {
+            known_tags.clone()
+        }) {
+            let tag = tag.trim();
+            self.view.tag = (!tag.is_empty()).then(|| tag.to_string());
+        }
+    }
+
+    // searches titles and descriptions as you type, Enter jumps to the first match from the
+    // selected task on and Esc puts the last search back
+    pub fn search_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) {
+        let before = self.search.clone();
+        let current = before.clone().unwrap_or_default();
+        let query = self.prompt_loop(terminal, " Search ", &current, |state, text| {
+            state.search = (!text.is_empty()).then(|| text.to_string());
+            match text {
+                "" => "titles and descriptions".to_string(),
+                query => match state.tasks.count_matches(query, &state.view) {
+                    0 => "no matches".to_string(),
+                    1 => "1 match".to_string(),
+                    count => format!("{count} matches"),
+                },
+            }
+        });
+        match query {
+            Some(_) => self.next_match_from(true, false),
+            None => self.search = before,
+        }
+    }
+
+    pub fn next_match(&mut self, forward: bool) {
+        self.next_match_from(forward, true);
+    }
+
+    fn next_match_from(&mut self, forward: bool, skip_selected: bool) {
+        let Some(query) = &self.search else {
+            return;
+        };
+        let found = self.tasks.find_match(
+            &self.selected_task,
+            query,
+            &self.view,
+            forward,
+            skip_selected,
+        );
+        match found {
+            Some(path) => self.jump_to(&path),
+            None => self.message = Some(format!("no tasks match {query:?}")),
+        }
+    }
+
+    // selects the task at path, opening up anything collapsed above it
+    fn jump_to(&mut self, path: &[usize]) {
+        let hidden = (1..path.len()).any(|depth| {
+            self.tasks
+                .get_task(&TaskIndexer::from_path(&path[..depth]))
+                .is_some_and(Task::is_collapsed)
+        });
+        if hidden {
+            self.tasks.expand_ancestors(path);
+            self.mark_view_changed();
+        }
+        self.selected_task = TaskIndexer::from_path(path);
+    }
+
+    // a single line prompt drawn over the list, returns None when it was cancelled. on_change
+    // gets the text after every key and gives back the hint shown under it
+    fn prompt_loop<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        title: &str,
+        initial: &str,
+        mut on_change: impl FnMut(&mut Self, &str) -> String,
+    ) -> Option<String> {
+        let mut input = TextInputField::from_string(initial);
         loop {
-            let _ = terminal.draw(|frame| buffered_task.draw(frame));
+            let hint = on_change(self, &input.text());
+            let _ = terminal.draw(|frame| {
+                self.draw(frame);
+                Self::draw_prompt(frame, title, &hint, &input);
+            });
 
-            match event::read().unwrap() {
+            let Ok(event) = event::read() else {
+                break None;
+            };
+            match event {
                 Event::Key(KeyEvent {
-                    code: KeyCode::Char('s'),
+                    code: KeyCode::Enter,
+                    ..
+                }) => break Some(input.text()),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Esc, ..
+                }) => break None,
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
                     modifiers: KeyModifiers::CONTROL,
                     ..
-                }) => break buffered_task.save(),
+                }) if !input.has_selection() => break None,
+                Event::Paste(text) => input.paste(&text),
+                Event::Key(key) => {
+                    input.handle_key(&key, &mut self.clipboard);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn draw_prompt(frame: &mut Frame, title: &str, hint: &str, input: &TextInputField) {
+        let [_, prompt_row, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+        ])
+        .areas(frame.area());
+        let [_, prompt_area, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(60),
+            Constraint::Fill(1),
+        ])
+        .areas(prompt_row);
+
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(Line::from(format!(" {hint} ")).dark_gray())
+            .border_style(Color::Magenta);
+        let input_area = block.inner(prompt_area);
+        frame.render_widget(Clear, prompt_area);
+        frame.render_widget(block, prompt_area);
+        input.draw_selected(frame, input_area);
+    }
+
+    pub fn add_task_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Option<Task> {
+        Self::task_form_loop(
+            terminal,
+            BufferedTask::new(self.tasks.statuses()),
+            &mut self.clipboard,
+        )
+    }
+
+    pub fn edit_task_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) {
+        let Some(selected_task) = self.tasks.get_task(&self.selected_task) else {
+            return;
+        };
+        let before = selected_task.clone();
+        if let Some(edited_task) = Self::task_form_loop(
+            terminal,
+            BufferedTask::from_task(selected_task, self.tasks.statuses()),
+            &mut self.clipboard,
+        ) {
+            // the rest of the form is kept, only the status change is refused
+            let mut edited_task = edited_task;
+            if self.tasks.status_is_derived(&self.selected_task)
+                && edited_task.status() != before.status()
+            {
+                edited_task.set_status(before.status());
+                self.message = Some(DERIVED_STATUS_MESSAGE.to_string());
+            }
+            let edit = Edit::Update {
+                path: self.selected_task.path(),
+                before,
+                after: edited_task.clone(),
+            };
+            let result = self.tasks.edit_task(edited_task, &self.selected_task);
+            self.handle_mutation(result, edit);
+        }
+    }
+
+    // returns true when the app should quit, asks first if there are unsaved changes
+    pub fn confirm_quit_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> bool {
+        if !self.dirty {
+            return true;
+        }
+        loop {
+            let _ = terminal.draw(|frame| {
+                self.draw(frame);
+                Self::draw_quit_prompt(frame);
+            });
+
+            let Ok(event) = event::read() else {
+                break false;
+            };
+            match event {
                 Event::Key(KeyEvent {
-                    code: KeyCode::Up | KeyCode::BackTab,
+                    code: KeyCode::Char('s' | 'y'),
                     ..
-                }) => buffered_task.next_field(),
+                }) => break self.save().is_ok(),
                 Event::Key(KeyEvent {
-                    code: KeyCode::Down | KeyCode::Enter | KeyCode::Tab,
+                    code: KeyCode::Char('d' | 'n'),
                     ..
-                }) => buffered_task.prev_field(),
+                }) => break true,
                 Event::Key(KeyEvent {
-                    code: KeyCode::Char('c'),
-                    modifiers: KeyModifiers::CONTROL,
+                    code: KeyCode::Char('c') | KeyCode::Esc,
                     ..
-                }) => break None,
+                }) => break false,
+                _ => (),
+            }
+        }
+    }
+
+    fn draw_quit_prompt(frame: &mut Frame) {
+        let [_, prompt_row, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+        ])
+        .areas(frame.area());
+        let [_, prompt_area, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(50),
+            Constraint::Fill(1),
+        ])
+        .areas(prompt_row);
+
+        let prompt = Paragraph::new(Line::from(vec![
+            " Save ".into(),
+            "<s>".green().bold(),
+            " Discard ".into(),
+            "<d>".green().bold(),
+            " Cancel ".into(),
+            "<c/Esc>".green().bold(),
+        ]))
+        .centered()
+        .block(
+            Block::bordered()
+                .title(" Unsaved changes ")
+                .border_style(Color::Yellow),
+        );
+        frame.render_widget(Clear, prompt_area);
+        frame.render_widget(prompt, prompt_area);
+    }
+
+    fn task_form_loop<B: Backend>(
+        terminal: &mut Terminal<B>,
+        mut buffered_task: BufferedTask,
+        clipboard: &mut String,
+    ) -> Option<Task> {
+        loop {
+            let _ = terminal.draw(|frame| buffered_task.draw(frame));
+
+            let Ok(event) = event::read() else {
+                break None;
+            };
+            match event {
                 Event::Key(KeyEvent {
-                    code:
-                        KeyCode::Char(c @ ('a'..='z' | '_' | '0'..='9' | ' ' | 'A'..='Z' | '?' | '!')),
+                    code: KeyCode::Char('s'),
+                    modifiers: KeyModifiers::CONTROL,
                     ..
-                }) => buffered_task.push_char(c),
+                }) => match buffered_task.save() {
+                    Ok(task) => break Some(task),
+                    Err(e) => buffered_task.set_message(e.to_string()),
+                },
+                Event::Key(KeyEvent {
+                    code: KeyCode::Up, ..
+                }) => buffered_task.up_arrow(),
                 Event::Key(KeyEvent {
-                    code: KeyCode::Right,
+                    code: KeyCode::BackTab,
                     ..
-                }) => buffered_task.right_arrow(),
+                }) => buffered_task.next_field(),
                 Event::Key(KeyEvent {
-                    code: KeyCode::Left,
+                    code: KeyCode::Down,
                     ..
-                }) => buffered_task.left_arrow(),
-
+                }) => buffered_task.down_arrow(),
                 Event::Key(KeyEvent {
-                    code: KeyCode::Backspace,
+                    code: KeyCode::Enter,
                     ..
-                }) => buffered_task.pop_char(),
+                }) => buffered_task.enter(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Tab, ..
+                }) => buffered_task.prev_field(),
                 Event::Key(KeyEvent {
-                    code: KeyCode::Delete,
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
                     ..
-                }) => buffered_task.delete_char(),
-
+                }) if !buffered_task.has_selection() => break None,
+                Event::Key(key) => buffered_task.handle_key(&key, clipboard),
+                Event::Paste(text) => buffered_task.paste(&text),
                 _ => (),
             }
         }
@@ -0,0 +1,85 @@
+use crate::TaskList;
+use crate::file_format;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
+
+// how many old copies of the list are kept next to it, .bak.1 is the newest
+pub const BACKUP_COUNT: usize = 5;
+
+pub fn backup_path(file: &str, backup: usize) -> String {
+    format!("{file}.bak.{backup}")
+}
+
+// the list is written to a temp file and renamed over the old one so a crash never
+// leaves a half written list behind. with backup set the old list is rotated into the
+// backups first, which is only done once per session so frequent saves don't push every
+// useful backup out
+pub fn save_atomic(tasks: &TaskList, file: &str, backup: bool) -> std::io::Result<()> {
+    let temp_path = format!("{file}.tmp");
+    let mut temp_file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&temp_path)?;
+    file_format::write_task_list(tasks, &mut temp_file)?;
+    temp_file.flush()?;
+    temp_file.sync_all()?;
+    drop(temp_file);
+
+    if backup && Path::new(file).exists() {
+        rotate_backups(file)?;
+    }
+    std::fs::rename(&temp_path, file)?;
+    sync_parent_dir(file);
+    Ok(())
+}
+
+fn rotate_backups(file: &str) -> std::io::Result<()> {
+    for backup in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(file, backup);
+        if Path::new(&from).exists() {
+            std::fs::rename(&from, backup_path(file, backup + 1))?;
+        }
+    }
+    std::fs::copy(file, backup_path(file, 1))?;
+    Ok(())
+}
+
+// makes the rename durable, not every platform lets a directory be opened so errors are ignored
+fn sync_parent_dir(file: &str) {
+    let parent = match Path::new(file).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if let Ok(dir) = File::open(parent) {
+        let _ = dir.sync_all();
+    }
+}
+
+pub fn load_backup(file: &str, backup: usize) -> std::io::Result<TaskList> {
+    let mut backup_file = File::open(backup_path(file, backup))?;
+    TaskList::load(&mut backup_file)
+}
+
+pub struct BackupInfo {
+    pub backup: usize,
+    pub modified: Option<SystemTime>,
+    pub task_count: Option<usize>,
+}
+
+pub fn list_backups(file: &str) -> Vec<BackupInfo> {
+    (1..=BACKUP_COUNT)
+        .filter_map(|backup| {
+            let metadata = std::fs::metadata(backup_path(file, backup)).ok()?;
+            Some(BackupInfo {
+                backup,
+                modified: metadata.modified().ok(),
+                task_count: load_backup(file, backup)
+                    .ok()
+                    .map(|tasks| tasks.task_count()),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,101 @@
+// tags are words starting with # or @ (#backend, @alice), they can be written anywhere in a
+// title or listed on their own in the task form
+
+fn is_tag(word: &str) -> bool {
+    word.len() > 1 && (word.starts_with('#') || word.starts_with('@'))
+}
+
+// the tags written in a title, in the order they appear
+pub fn tags_in(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    text.split_whitespace()
+        .map(|word| word.trim_end_matches([',', '.', ';', ':', '!', '?']))
+        .filter(|word| is_tag(word))
+        .for_each(|tag| {
+            if !tags.iter().any(|known| known.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.to_string());
+            }
+        });
+    tags
+}
+
+// a list typed into the tag field, words without a # or @ are taken as # tags
+pub fn parse_tag_list(text: &str) -> Vec<String> {
+    let words: Vec<String> = text
+        .split([' ', ','])
+        // a # or @ on its own is a tag that hasn't been typed yet
+        .filter(|word| !word.is_empty() && *word != "#" && *word != "@")
+        .map(|word| match is_tag(word) {
+            true => word.to_string(),
+            false => format!("#{word}"),
+        })
+        .collect();
+    tags_in(&words.join(" "))
+}
+
+// "backend" finds #backend, "#backend" and "@alice" have to match exactly, case aside
+pub fn matches(tag: &str, query: &str) -> bool {
+    match is_tag(query) {
+        true => tag.eq_ignore_ascii_case(query),
+        false => tag[1..].eq_ignore_ascii_case(query),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_in_titles() {
+        assert_eq!(
+            tags_in("fix login #backend for @alice"),
+            ["#backend", "@alice"]
+        );
+        assert_eq!(tags_in("no tags here, just text"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn trailing_punctuation_is_not_part_of_a_tag() {
+        assert_eq!(
+            tags_in("ask @alice, then @bob. #urgent! #why? #a:"),
+            ["@alice", "@bob", "#urgent", "#why", "#a"]
+        );
+        assert_eq!(tags_in("#v1.2 user@example.com"), ["#v1.2"]);
+    }
+
+    #[test]
+    fn a_lone_sign_is_not_a_tag() {
+        assert_eq!(tags_in("# @ #! @,"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn repeats_are_dropped_case_aside() {
+        assert_eq!(tags_in("#Work and #work, #WORK"), ["#Work"]);
+    }
+
+    #[test]
+    fn tag_lists_add_a_hash_to_bare_words() {
+        assert_eq!(
+            parse_tag_list("backend, @alice #ui,,frontend"),
+            ["#backend", "@alice", "#ui", "#frontend"]
+        );
+        assert_eq!(parse_tag_list(" backend  Backend "), ["#backend"]);
+        assert_eq!(parse_tag_list(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn tag_lists_skip_lone_signs() {
+        assert_eq!(parse_tag_list("# @ work"), ["#work"]);
+    }
+
+    #[test]
+    fn bare_queries_ignore_the_sign() {
+        assert!(matches("#backend", "backend"));
+        assert!(matches("@alice", "alice"));
+        assert!(matches("#Backend", "BACKEND"));
+        assert!(matches("#backend", "#backend"));
+        assert!(!matches("@alice", "#alice"));
+        assert!(matches("@alice", "@Alice"));
+        assert!(!matches("#backend", "back"));
+    }
+}
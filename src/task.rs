@@ -1,10 +1,33 @@
 use crate::BorshDeserialize;
+use crate::error::{DootError, Result};
+use crate::priority::Priority;
+use crate::search;
+use crate::tags;
+use crate::task_date::TaskDate;
 use crate::task_list::TaskIndexer;
-use crate::task_status::TaskStatus;
+use crate::task_status::{Statuses, TaskStatus};
+use crate::view::View;
+use crate::viewport::TaskRow;
+use crate::wrap;
 use borsh::BorshSerialize;
 use ratatui::prelude::*;
+use ratatui::symbols;
 use ratatui::widgets::Block;
+use ratatui::widgets::LineGauge;
 use ratatui::widgets::Paragraph;
+use unicode_width::UnicodeWidthStr;
+
+// when a task was finished, kept while its status counts as done
+#[derive(Default, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq)]
+pub enum Finished {
+    #[default]
+    No,
+    At(i64), // unix seconds
+    Unknown, // already done in a file from before finish times were kept
+}
+
+// columns the progress gauge takes up in a parent's title bar, label included
+const GAUGE_WIDTH: u16 = 24;
 
 #[derive(Default, BorshSerialize, BorshDeserialize, Clone)]
 pub struct Task {
@@ -12,17 +35,34 @@ pub struct Task {
     status: TaskStatus,
     desc: String,
     children: Vec<Task>,
+    collapsed: bool,
+    due: Option<TaskDate>,
+    scheduled: Option<TaskDate>, // the day work is meant to start
+    priority: Priority,
+    tags: Vec<String>, // includes the ones written in the title
+    finished: Finished,
 }
 
 impl Task {
-    pub fn incr_level(&self, task_indexer: &mut TaskIndexer) {
+    fn child_at(&self, index: usize) -> Result<&Task> {
+        self.children.get(index).ok_or(DootError::InvalidSelection)
+    }
+
+    fn child_at_mut(&mut self, index: usize) -> Result<&mut Task> {
+        self.children
+            .get_mut(index)
+            .ok_or(DootError::InvalidSelection)
+    }
+
+    pub fn incr_level(&self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
-            TaskIndexer::Null | TaskIndexer::Selected => panic!(),
+            TaskIndexer::Null | TaskIndexer::Selected => return Err(DootError::InvalidSelection),
             TaskIndexer::Child(selected_child_index, task_indexer) => {
-                self.children[*selected_child_index].incr_level(task_indexer);
+                self.child_at(*selected_child_index)?
+                    .incr_level(task_indexer)?;
             }
             TaskIndexer::SelectedChild(selected_child_index) => {
-                if self.children[*selected_child_index].child_count() != 0 {
+                if self.child_at(*selected_child_index)?.has_visible_children() {
                     *task_indexer = TaskIndexer::Child(
                         *selected_child_index,
                         Box::new(TaskIndexer::SelectedChild(0)),
@@ -30,23 +70,27 @@ impl Task {
                 }
             }
         }
+        Ok(())
     }
 
-    pub fn decr_level(&self, task_indexer: &mut TaskIndexer) {
+    pub fn decr_level(&self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
-            TaskIndexer::Null | TaskIndexer::Selected => panic!(),
+            TaskIndexer::Null | TaskIndexer::Selected => return Err(DootError::InvalidSelection),
             TaskIndexer::Child(child_index, child_task_indexer) => match &**child_task_indexer {
                 TaskIndexer::SelectedChild(_) => {
                     **child_task_indexer = TaskIndexer::Selected;
                     *task_indexer = TaskIndexer::SelectedChild(*child_index);
                 }
                 TaskIndexer::Child(_, _) => {
-                    self.children[*child_index].decr_level(child_task_indexer);
+                    self.child_at(*child_index)?
+                        .decr_level(child_task_indexer)?;
+                }
+                TaskIndexer::Null | TaskIndexer::Selected => {
+                    return Err(DootError::InvalidSelection);
                 }
-                TaskIndexer::Null | TaskIndexer::Selected => panic!(),
             },
             TaskIndexer::SelectedChild(selected_child_index) => {
-                if self.children[*selected_child_index].child_count() != 0 {
+                if self.child_at(*selected_child_index)?.has_visible_children() {
                     *task_indexer = TaskIndexer::Child(
                         *selected_child_index,
                         Box::new(TaskIndexer::SelectedChild(0)),
@@ -54,42 +98,299 @@ impl Task {
                 }
             }
         }
+        Ok(())
     }
 
-    pub fn delete_task(&mut self, task_indexer: &mut TaskIndexer) {
+    pub fn delete_task(&mut self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
-            TaskIndexer::Null | TaskIndexer::Selected => panic!(),
+            TaskIndexer::Null | TaskIndexer::Selected => return Err(DootError::InvalidSelection),
             TaskIndexer::Child(selected_child_index, child_task_indexer) => {
                 match **child_task_indexer {
                     TaskIndexer::Null | TaskIndexer::Selected => (),
-                    TaskIndexer::Child(_, _) => {
-                        self.children[*selected_child_index].delete_task(child_task_indexer)
-                    }
+                    TaskIndexer::Child(_, _) => self
+                        .child_at_mut(*selected_child_index)?
+                        .delete_task(child_task_indexer)?,
                     TaskIndexer::SelectedChild(child_selected_child_index) => {
-                        self.children[*selected_child_index]
-                            .children
-                            .remove(child_selected_child_index);
-                        if self.children[*selected_child_index].child_count() == 0 {
+                        let selected_child = self.child_at_mut(*selected_child_index)?;
+                        selected_child.remove_child(child_selected_child_index)?;
+                        if selected_child.child_count() == 0 {
                             *task_indexer = TaskIndexer::SelectedChild(*selected_child_index);
                         }
                     }
                 }
             }
             TaskIndexer::SelectedChild(selected_child_index) => {
-                self.children.remove(*selected_child_index);
-                if *selected_child_index == self.children.len() {
+                self.remove_child(*selected_child_index)?;
+                if *selected_child_index == self.children.len() && *selected_child_index != 0 {
                     *selected_child_index -= 1;
                 }
             }
         };
+        Ok(())
     }
 
     pub fn new(title: String, status: TaskStatus, desc: String) -> Self {
         Self {
+            tags: tags::tags_in(&title),
             title,
             status,
             desc,
             children: vec![],
+            collapsed: false,
+            due: None,
+            scheduled: None,
+            priority: Priority::None,
+            finished: Finished::No,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn status(&self) -> TaskStatus {
+        self.status
+    }
+
+    pub fn desc(&self) -> &str {
+        &self.desc
+    }
+
+    pub fn due(&self) -> Option<TaskDate> {
+        self.due
+    }
+
+    pub fn set_due(&mut self, due: Option<TaskDate>) {
+        self.due = due;
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    pub fn add_tags(&mut self, new_tags: &[String]) {
+        let all_tags = [self.tags.as_slice(), new_tags].concat();
+        self.tags = tags::tags_in(&all_tags.join(" "));
+    }
+
+    // only tags added on their own can be removed, the ones in the title stay with it
+    pub fn remove_tags(&mut self, queries: &[String]) -> Result<()> {
+        let title_tags = tags::tags_in(&self.title);
+        if let Some(tag) = title_tags
+            .iter()
+            .find(|tag| queries.iter().any(|query| tags::matches(tag, query)))
+        {
+            return Err(DootError::TagInTitle(tag.clone()));
+        }
+        self.tags
+            .retain(|tag| !queries.iter().any(|query| tags::matches(tag, query)));
+        Ok(())
+    }
+
+    pub fn matches_search(&self, query: &str) -> bool {
+        search::matches(&self.title, query) || search::matches(&self.desc, query)
+    }
+
+    pub fn has_tag(&self, query: &str) -> bool {
+        self.tags.iter().any(|tag| tags::matches(tag, query))
+    }
+
+    // adds the tags of this task and everything under it that aren't in found yet
+    pub fn collect_tags(&self, found: &mut Vec<String>) {
+        self.tags.iter().for_each(|tag| {
+            if !found.iter().any(|known| known.eq_ignore_ascii_case(tag)) {
+                found.push(tag.clone());
+            }
+        });
+        self.children
+            .iter()
+            .for_each(|child| child.collect_tags(found));
+    }
+
+    // tags that were added on their own and so aren't already shown in the title
+    pub fn extra_tags(&self) -> Vec<&str> {
+        let title_tags = tags::tags_in(&self.title);
+        self.tags
+            .iter()
+            .filter(|tag| {
+                !title_tags
+                    .iter()
+                    .any(|title_tag| title_tag.eq_ignore_ascii_case(tag))
+            })
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn scheduled(&self) -> Option<TaskDate> {
+        self.scheduled
+    }
+
+    pub fn set_scheduled(&mut self, scheduled: Option<TaskDate>) {
+        self.scheduled = scheduled;
+    }
+
+    // due today or earlier and not done yet
+    pub fn is_due_by(&self, date: TaskDate, statuses: &Statuses) -> bool {
+        self.due.is_some_and(|due| due <= date) && !statuses.is_done(self.status)
+    }
+
+    pub fn children(&self) -> &[Task] {
+        &self.children
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+    }
+
+    pub fn has_visible_children(&self) -> bool {
+        !self.collapsed && !self.children.is_empty()
+    }
+
+    pub fn expand_to_depth(&mut self, depth: usize) {
+        self.collapsed = depth == 0;
+        self.children
+            .iter_mut()
+            .for_each(|child| child.expand_to_depth(depth.saturating_sub(1)));
+    }
+
+    pub fn set_status(&mut self, status: TaskStatus) {
+        self.status = status;
+    }
+
+    pub fn finished(&self) -> Finished {
+        self.finished
+    }
+
+    pub fn set_finished(&mut self, finished: Finished) {
+        self.finished = finished;
+    }
+
+    // a task that just became done is stamped with now (or Unknown without one), one that
+    // isn't done anymore loses its stamp
+    pub fn stamp_finished(&mut self, statuses: &Statuses, now: Option<i64>) {
+        self.finished = match (statuses.is_done(self.status), self.finished) {
+            (false, _) => Finished::No,
+            (true, Finished::No) => now.map_or(Finished::Unknown, Finished::At),
+            (true, finished) => finished,
+        };
+        self.children
+            .iter_mut()
+            .for_each(|child| child.stamp_finished(statuses, now));
+    }
+
+    pub fn replace_status(&mut self, from: TaskStatus, to: TaskStatus) {
+        if self.status == from {
+            self.status = to;
+        }
+        self.children
+            .iter_mut()
+            .for_each(|child| child.replace_status(from, to));
+    }
+
+    // the children are derived first so the status rolls all the way up
+    pub fn derive_status(&mut self, statuses: &Statuses) {
+        if self.children.is_empty() {
+            return;
+        }
+        self.children
+            .iter_mut()
+            .for_each(|child| child.derive_status(statuses));
+        let child_statuses: Vec<TaskStatus> =
+            self.children.iter().map(|child| child.status).collect();
+        self.status = statuses.derived(&child_statuses);
+    }
+
+    // done and total leaf tasks under this one, None when there are no children to count
+    pub fn progress(&self, statuses: &Statuses) -> Option<(usize, usize)> {
+        if self.children.is_empty() {
+            return None;
+        }
+        Some(
+            self.children
+                .iter()
+                .map(|child| child.leaf_progress(statuses))
+                .fold((0, 0), |(done, total), (d, t)| (done + d, total + t)),
+        )
+    }
+
+    // a parent marked done by hand counts all of its leaves as done
+    fn leaf_progress(&self, statuses: &Statuses) -> (usize, usize) {
+        let done = statuses.is_done(self.status);
+        match self.progress(statuses) {
+            None => (done as usize, 1),
+            Some((_, total)) if done => (total, total),
+            Some(progress) => progress,
+        }
+    }
+
+    pub fn add_child(&mut self, new_task: Task) {
+        self.children.push(new_task);
+    }
+
+    // only the task's own fields are replaced, children are left where they are
+    pub fn update(&mut self, edited_task: Task) {
+        let Task {
+            title,
+            status,
+            desc,
+            due,
+            scheduled,
+            priority,
+            tags,
+            finished,
+            ..
+        } = edited_task;
+        self.title = title;
+        self.status = status;
+        self.desc = desc;
+        self.due = due;
+        self.scheduled = scheduled;
+        self.priority = priority;
+        self.tags = tags;
+        self.finished = finished;
+    }
+
+    pub fn get_task(&self, task_indexer: &TaskIndexer) -> Option<&Task> {
+        match task_indexer {
+            TaskIndexer::SelectedChild(selected_child_index) => {
+                self.children.get(*selected_child_index)
+            }
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .children
+                .get(*child_index)
+                .and_then(|child| child.get_task(child_task_indexer)),
+            TaskIndexer::Selected => Some(self),
+            TaskIndexer::Null => None,
+        }
+    }
+
+    pub fn get_task_mut(&mut self, task_indexer: &TaskIndexer) -> Option<&mut Task> {
+        match task_indexer {
+            TaskIndexer::SelectedChild(selected_child_index) => {
+                self.children.get_mut(*selected_child_index)
+            }
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .children
+                .get_mut(*child_index)
+                .and_then(|child| child.get_task_mut(child_task_indexer)),
+            TaskIndexer::Selected => Some(self),
+            TaskIndexer::Null => None,
         }
     }
 
@@ -101,24 +402,24 @@ impl Task {
         self.children.len()
     }
 
-    pub fn counts_vec(&self) -> Vec<u16> {
-        self.children.iter().map(|c| c.count()).collect()
-    }
-
-    pub fn decr(&mut self, task_indexer: &mut TaskIndexer) {
+    pub fn decr(&mut self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(selected_child_index) => {
+                if self.children.is_empty() {
+                    return Err(DootError::InvalidSelection);
+                }
                 *selected_child_index = (*selected_child_index + 1) % self.children.len();
             }
             TaskIndexer::Child(selected_child_index, task_indexer) => {
-                self.children[*selected_child_index].decr(task_indexer);
+                self.child_at_mut(*selected_child_index)?
+                    .decr(task_indexer)?;
             }
-            TaskIndexer::Selected => todo!(),
-            TaskIndexer::Null => todo!(),
+            TaskIndexer::Selected | TaskIndexer::Null => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn incr(&mut self, task_indexer: &mut TaskIndexer) {
+    pub fn incr(&mut self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(selected_child_index) => {
                 if *selected_child_index != 0 {
@@ -128,168 +429,274 @@ impl Task {
                 }
             }
             TaskIndexer::Child(selected_child_index, task_indexer) => {
-                self.children[*selected_child_index].incr(task_indexer);
+                self.child_at_mut(*selected_child_index)?
+                    .incr(task_indexer)?;
             }
-            TaskIndexer::Selected => todo!(),
-            TaskIndexer::Null => todo!(),
+            TaskIndexer::Selected | TaskIndexer::Null => return Err(DootError::InvalidSelection),
+        }
+        Ok(())
+    }
+
+    // rows the task takes up in the task list view at width columns, border included. the
+    // description always gets at least two rows and more when it wraps onto them
+    pub fn height(&self, width: u16) -> u16 {
+        let desc_lines = wrap::wrap(&self.desc, width.saturating_sub(2) as usize).len();
+        2 + desc_lines.max(2) as u16
+    }
+
+    // flattens this task and everything under it into rows in the order they're drawn
+    pub fn push_rows<'a>(
+        &'a self,
+        path: Vec<usize>,
+        selected_path: &[usize],
+        rows: &mut Vec<TaskRow<'a>>,
+        view: &View,
+        statuses: &Statuses,
+    ) {
+        rows.push(TaskRow {
+            task: self,
+            selected: path == selected_path,
+            path: path.clone(),
+        });
+        if self.collapsed {
+            return;
         }
+        view.order(&self.children, statuses)
+            .into_iter()
+            .for_each(|i| {
+                let mut child_path = path.clone();
+                child_path.push(i);
+                self.children[i].push_rows(child_path, selected_path, rows, view, statuses);
+            });
+    }
+
+    pub fn is_finished(&self, statuses: &Statuses) -> bool {
+        statuses.is_done(self.status)
+            && self
+                .children
+                .iter()
+                .all(|child| child.is_finished(statuses))
     }
 
-    pub fn draw(
+    pub fn push_finished_paths(
         &self,
-        frame: &mut Frame,
-        paintable_area: &Rect,
-        child_offset: i32,
-        selected_task: &TaskIndexer,
+        path: Vec<usize>,
+        statuses: &Statuses,
+        paths: &mut Vec<Vec<usize>>,
     ) {
-        let mut v_splits = vec![Constraint::Length(4)];
-        self.counts_vec().iter().for_each(|c| {
-            v_splits.push(Constraint::Length(4 * c));
+        if self.is_finished(statuses) {
+            paths.push(path);
+            return;
+        }
+        self.children.iter().enumerate().for_each(|(i, child)| {
+            let mut child_path = path.clone();
+            child_path.push(i);
+            child.push_finished_paths(child_path, statuses, paths);
         });
+    }
 
-        let task_rows = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(v_splits)
-            .split(*paintable_area);
+    // like push_rows but also goes into collapsed tasks, for finding tasks that aren't drawn
+    pub fn push_paths(
+        &self,
+        path: Vec<usize>,
+        paths: &mut Vec<Vec<usize>>,
+        view: &View,
+        statuses: &Statuses,
+    ) {
+        paths.push(path.clone());
+        view.order(&self.children, statuses)
+            .into_iter()
+            .for_each(|i| {
+                let mut child_path = path.clone();
+                child_path.push(i);
+                self.children[i].push_paths(child_path, paths, view, statuses);
+            });
+    }
 
-        let this_task_row = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Length(child_offset as u16), Constraint::Fill(1)])
-            .split(task_rows[0]);
+    // search picks out the text matching it in the title and description
+    pub fn draw_block(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        selected: bool,
+        statuses: &Statuses,
+        search: Option<&str>,
+    ) {
+        let mut block = Block::bordered().title(self.title_line(search));
+        if let Some(hidden_line) = self.hidden_line() {
+            block = block.title(hidden_line);
+        }
+        block = block.title(statuses.to_line(self.status));
+        let today = TaskDate::today();
+        if let Some(scheduled) = self.scheduled {
+            block = block.title_bottom(
+                Line::from(format!(" starts {} ", scheduled.describe(today))).dark_gray(),
+            );
+        }
+        if let Some(due_line) = self.due_line(today, statuses) {
+            block = block.title_bottom(due_line.right_aligned());
+        }
+        match (selected, self.due_color(today, statuses)) {
+            (true, _) => block = block.border_style(Color::Cyan),
+            (false, Some(color)) => block = block.border_style(color),
+            (false, None) => (),
+        }
+        let desc: Vec<Line> = wrap::wrap(&self.desc, area.width.saturating_sub(2) as usize)
+            .into_iter()
+            .map(|(start, end)| Line::from(search::highlight(&self.desc[start..end], search)))
+            .collect();
+        frame.render_widget(Paragraph::new(desc).block(block), area);
+        if let Some(progress) = self.progress(statuses)
+            && let Some(gauge_area) = self.gauge_area(area, statuses)
+        {
+            frame.render_widget(Self::progress_gauge(progress), gauge_area);
+        }
+    }
 
-        match selected_task {
-            TaskIndexer::SelectedChild(selected_child_index) => {
-                let block_para = self.simple_block();
-                frame.render_widget(block_para, this_task_row[1]);
-                self.children
-                    .iter()
-                    .zip(task_rows[1..].iter())
-                    .enumerate()
-                    .for_each(|(i, (child_task, child_layout))| {
-                        if i != *selected_child_index {
-                            child_task.draw(
-                                frame,
-                                child_layout,
-                                child_offset + 4,
-                                &TaskIndexer::Null,
-                            );
-                        } else {
-                            child_task.draw(
-                                frame,
-                                child_layout,
-                                child_offset + 4,
-                                &TaskIndexer::Selected,
-                            );
-                        }
-                    });
-            }
-            TaskIndexer::Child(selected_child_index, task_indexer) => {
-                let block_para = self.simple_block();
-                frame.render_widget(block_para, this_task_row[1]);
-                self.children
-                    .iter()
-                    .zip(task_rows[1..].iter())
-                    .enumerate()
-                    .for_each(|(i, (child_task, child_layout))| {
-                        if i != *selected_child_index {
-                            child_task.draw(
-                                frame,
-                                child_layout,
-                                child_offset + 4,
-                                &TaskIndexer::Null,
-                            );
-                        } else {
-                            child_task.draw(frame, child_layout, child_offset + 4, task_indexer);
-                        }
-                    });
-            }
-            TaskIndexer::Selected => {
-                let block_para = self.selected_block();
-                frame.render_widget(block_para, this_task_row[1]);
-                self.children.iter().zip(task_rows[1..].iter()).for_each(
-                    |(child_task, child_layout)| {
-                        child_task.draw(frame, child_layout, child_offset + 4, &TaskIndexer::Null);
-                    },
-                );
-            }
-            TaskIndexer::Null => {
-                let block_para = self.simple_block();
-                frame.render_widget(block_para, this_task_row[1]);
-                self.children.iter().zip(task_rows[1..].iter()).for_each(
-                    |(child_task, child_layout)| {
-                        child_task.draw(frame, child_layout, child_offset + 4, &TaskIndexer::Null);
-                    },
-                );
-            }
+    // red once a task is overdue and yellow on the day it's due, done tasks aren't highlighted
+    fn due_color(&self, today: TaskDate, statuses: &Statuses) -> Option<Color> {
+        let due = self.due?;
+        match self.is_due_by(today, statuses) {
+            true if due < today => Some(Color::Red),
+            true => Some(Color::Yellow),
+            false => None,
         }
     }
 
-    fn simple_block(&self) -> Paragraph {
-        Paragraph::new(&*self.desc).block(
-            Block::bordered()
-                .title(&*self.title)
-                .title(self.status.to_line()),
-        )
+    fn due_line(&self, today: TaskDate, statuses: &Statuses) -> Option<Line<'_>> {
+        let due = self.due?;
+        let line = match self.due_color(today, statuses) {
+            Some(Color::Red) => Line::from(format!(" overdue, due {} ", due.describe(today))),
+            _ => Line::from(format!(" due {} ", due.describe(today))),
+        };
+        Some(match self.due_color(today, statuses) {
+            Some(color) => line.style(color).bold(),
+            None => line.dark_gray(),
+        })
     }
 
-    fn selected_block(&self) -> Paragraph {
-        Paragraph::new(&*self.desc).block(
-            Block::bordered()
-                .title(&*self.title)
-                .title(self.status.to_line())
-                .border_style(Color::Cyan),
-        )
+    // the gauge sits in the top border just left of the status, it's left out when the block
+    // is too narrow to fit it without covering the title
+    fn gauge_area(&self, area: Rect, statuses: &Statuses) -> Option<Rect> {
+        let status_width = statuses.name(self.status).width() as u16;
+        let title_width = self.title_line(None).width() as u16
+            + self.hidden_line().map_or(0, |line| line.width() as u16);
+        if area.width < title_width + GAUGE_WIDTH + status_width + 4 {
+            return None;
+        }
+        Some(Rect {
+            x: area.right() - 2 - status_width - GAUGE_WIDTH,
+            y: area.y,
+            width: GAUGE_WIDTH,
+            height: 1,
+        })
+    }
+
+    fn progress_gauge<'a>((done, total): (usize, usize)) -> LineGauge<'a> {
+        let ratio = done as f64 / total as f64;
+        LineGauge::default()
+            .ratio(ratio)
+            .label(format!(" {done}/{total} {:.0}%", ratio * 100.0))
+            .line_set(symbols::line::THICK)
+            .filled_style(Color::Green)
+            .unfilled_style(Color::DarkGray)
+    }
+
+    fn title_line(&self, search: Option<&str>) -> Line<'_> {
+        let mut spans = vec![];
+        if let Some(badge) = self.priority.badge() {
+            spans.push(badge);
+            spans.push(" ".into());
+        }
+        spans.extend(search::highlight(&self.title, search));
+        self.extra_tags()
+            .into_iter()
+            .for_each(|tag| spans.push(Span::from(format!(" {tag}")).magenta()));
+        Line::from(spans)
+    }
+
+    fn hidden_line(&self) -> Option<Line<'_>> {
+        match self.collapsed && !self.children.is_empty() {
+            true => Some(Line::from(format!(" (+{} hidden) ", self.count() - 1)).dark_gray()),
+            false => None,
+        }
     }
 
-    pub fn append_task(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) {
+    pub fn append_task(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(_) => self.children.push(new_task),
-            TaskIndexer::Child(child_index, child_task_indexer) => {
-                self.children[*child_index].append_task(new_task, child_task_indexer)
-            }
-            TaskIndexer::Selected | TaskIndexer::Null => panic!(),
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .child_at_mut(*child_index)?
+                .append_task(new_task, child_task_indexer)?,
+            TaskIndexer::Selected | TaskIndexer::Null => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn prepend_task(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) {
+    pub fn prepend_task(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(_) => self.children.insert(0, new_task),
-            TaskIndexer::Child(child_index, child_task_indexer) => {
-                self.children[*child_index].prepend_task(new_task, child_task_indexer)
-            }
-            TaskIndexer::Selected | TaskIndexer::Null => panic!(),
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .child_at_mut(*child_index)?
+                .prepend_task(new_task, child_task_indexer)?,
+            TaskIndexer::Selected | TaskIndexer::Null => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn insert_task_above(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) {
+    pub fn insert_task_above(
+        &mut self,
+        new_task: Task,
+        task_indexer: &mut TaskIndexer,
+    ) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(selected_child_index) => {
+                if *selected_child_index > self.children.len() {
+                    return Err(DootError::InvalidSelection);
+                }
                 self.children.insert(*selected_child_index, new_task)
             }
-            TaskIndexer::Child(child_index, child_task_indexer) => {
-                self.children[*child_index].insert_task_above(new_task, child_task_indexer)
-            }
-            TaskIndexer::Selected | TaskIndexer::Null => panic!(),
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .child_at_mut(*child_index)?
+                .insert_task_above(new_task, child_task_indexer)?,
+            TaskIndexer::Selected | TaskIndexer::Null => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn insert_task_below(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) {
+    pub fn insert_task_below(
+        &mut self,
+        new_task: Task,
+        task_indexer: &mut TaskIndexer,
+    ) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(selected_child_index) => {
-                if *selected_child_index != self.children.len() - 1 {
+                if *selected_child_index + 1 < self.children.len() {
                     self.children.insert((*selected_child_index) + 1, new_task)
                 } else {
                     self.children.push(new_task)
                 }
             }
-            TaskIndexer::Child(child_index, child_task_indexer) => {
-                self.children[*child_index].insert_task_below(new_task, child_task_indexer)
-            }
-            TaskIndexer::Selected | TaskIndexer::Null => panic!(),
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .child_at_mut(*child_index)?
+                .insert_task_below(new_task, child_task_indexer)?,
+            TaskIndexer::Selected | TaskIndexer::Null => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn remove_child(&mut self, index: usize) {
-        self.children.remove(index);
+    pub fn insert_child(&mut self, index: usize, new_task: Task) -> Result<()> {
+        if index > self.children.len() {
+            return Err(DootError::InvalidSelection);
+        }
+        self.children.insert(index, new_task);
+        Ok(())
+    }
+
+    pub fn remove_child(&mut self, index: usize) -> Result<Task> {
+        if index >= self.children.len() {
+            return Err(DootError::InvalidSelection);
+        }
+        Ok(self.children.remove(index))
     }
 }
@@ -0,0 +1,177 @@
+use crate::error::{DootError, Result};
+use borsh::BorshDeserialize;
+use borsh::BorshSerialize;
+use chrono::{Datelike, Local, NaiveDate, Weekday};
+
+// a calendar day without a time, saved as days since 0001-01-01 so borsh can write it
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TaskDate(i32);
+
+impl TaskDate {
+    pub fn today() -> Self {
+        Self::from_naive(Local::now().date_naive())
+    }
+
+    fn from_naive(date: NaiveDate) -> Self {
+        Self(date.num_days_from_ce())
+    }
+
+    fn to_naive(self) -> Option<NaiveDate> {
+        NaiveDate::from_num_days_from_ce_opt(self.0)
+    }
+
+    fn add_days(self, days: i32) -> Self {
+        Self(self.0.saturating_add(days))
+    }
+
+    // accepts today, tomorrow, yesterday, weekday names ("fri" is the next friday, today
+    // included), +3d/+2w offsets and yyyy-mm-dd, empty input or none means no date
+    pub fn parse(input: &str, today: Self) -> Result<Option<Self>> {
+        let input = input.trim().to_lowercase();
+        let invalid = || DootError::InvalidDate(input.clone());
+        let date = match input.as_str() {
+            "" | "none" => return Ok(None),
+            "today" => today,
+            "tomorrow" | "tmr" => today.add_days(1),
+            "yesterday" => today.add_days(-1),
+            "next week" => today.add_days(7),
+            offset if offset.starts_with('+') => {
+                let count = |count: &str| count.parse::<i32>().map_err(|_| invalid());
+                match (offset[1..].strip_suffix('d'), offset[1..].strip_suffix('w')) {
+                    (Some(days), _) => today.add_days(count(days)?),
+                    (_, Some(weeks)) => today.add_days(count(weeks)?.saturating_mul(7)),
+                    _ => return Err(invalid()),
+                }
+            }
+            day => match day.parse::<Weekday>() {
+                Ok(weekday) => {
+                    let today_weekday = today.to_naive().ok_or_else(invalid)?.weekday();
+                    let days_ahead = (weekday.num_days_from_monday() + 7
+                        - today_weekday.num_days_from_monday())
+                        % 7;
+                    today.add_days(days_ahead as i32)
+                }
+                Err(_) => Self::from_naive(
+                    NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|_| invalid())?,
+                ),
+            },
+        };
+        match date.to_naive() {
+            Some(_) => Ok(Some(date)),
+            None => Err(invalid()),
+        }
+    }
+
+    // weekday names are close enough for the coming week, further out gets the full date
+    pub fn describe(self, today: Self) -> String {
+        match self.0.saturating_sub(today.0) {
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            -1 => "yesterday".to_string(),
+            2..7 => self
+                .to_naive()
+                .map(|date| date.format("%a").to_string())
+                .unwrap_or_default(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for TaskDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_naive() {
+            Some(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            None => write!(f, "invalid date"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a wednesday
+    fn today() -> TaskDate {
+        TaskDate::from_naive(NaiveDate::from_ymd_opt(2026, 10, 14).unwrap())
+    }
+
+    fn parsed(input: &str) -> Option<String> {
+        TaskDate::parse(input, today())
+            .unwrap_or_else(|e| panic!("{input}: {e}"))
+            .map(|date| date.to_string())
+    }
+
+    #[test]
+    fn empty_and_none_clear_the_date() {
+        assert_eq!(parsed(""), None);
+        assert_eq!(parsed("  "), None);
+        assert_eq!(parsed("None"), None);
+    }
+
+    #[test]
+    fn relative_days() {
+        assert_eq!(parsed("today").as_deref(), Some("2026-10-14"));
+        assert_eq!(parsed(" Tomorrow ").as_deref(), Some("2026-10-15"));
+        assert_eq!(parsed("tmr").as_deref(), Some("2026-10-15"));
+        assert_eq!(parsed("yesterday").as_deref(), Some("2026-10-13"));
+        assert_eq!(parsed("next week").as_deref(), Some("2026-10-21"));
+    }
+
+    #[test]
+    fn offsets_cross_month_and_year_ends() {
+        assert_eq!(parsed("+3d").as_deref(), Some("2026-10-17"));
+        assert_eq!(parsed("+20d").as_deref(), Some("2026-11-03"));
+        assert_eq!(parsed("+12w").as_deref(), Some("2027-01-06"));
+        assert_eq!(parsed("+0d").as_deref(), Some("2026-10-14"));
+        assert_eq!(parsed("+-1d").as_deref(), Some("2026-10-13"));
+    }
+
+    #[test]
+    fn weekdays_are_the_next_one_with_today_included() {
+        assert_eq!(parsed("wed").as_deref(), Some("2026-10-14"));
+        assert_eq!(parsed("thu").as_deref(), Some("2026-10-15"));
+        assert_eq!(parsed("friday").as_deref(), Some("2026-10-16"));
+        assert_eq!(parsed("sun").as_deref(), Some("2026-10-18"));
+        // earlier in the week rolls over to next week
+        assert_eq!(parsed("mon").as_deref(), Some("2026-10-19"));
+        assert_eq!(parsed("tue").as_deref(), Some("2026-10-20"));
+    }
+
+    #[test]
+    fn full_dates() {
+        assert_eq!(parsed("2026-11-01").as_deref(), Some("2026-11-01"));
+        assert_eq!(parsed("2028-02-29").as_deref(), Some("2028-02-29"));
+    }
+
+    #[test]
+    fn nonsense_is_an_error() {
+        for input in [
+            "+3",
+            "+d",
+            "+3m",
+            "+xd",
+            "soon",
+            "2026-02-30",
+            "2026-13-01",
+            "14/10/2026",
+        ] {
+            assert!(TaskDate::parse(input, today()).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn describe_uses_words_for_the_coming_week() {
+        let describe = |input: &str| {
+            TaskDate::parse(input, today())
+                .unwrap()
+                .unwrap()
+                .describe(today())
+        };
+        assert_eq!(describe("today"), "today");
+        assert_eq!(describe("tomorrow"), "tomorrow");
+        assert_eq!(describe("yesterday"), "yesterday");
+        assert_eq!(describe("+2d"), "Fri");
+        assert_eq!(describe("+7d"), "2026-10-21");
+        assert_eq!(describe("2026-10-10"), "2026-10-10");
+    }
+}
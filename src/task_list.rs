@@ -1,26 +1,122 @@
-use std::fs::OpenOptions;
-
 use crate::Task;
+use crate::archive::ArchivedTask;
+use crate::error::{DootError, Result};
+use crate::file_format;
+use crate::settings::{ListSettings, ParentStatus};
+use crate::storage;
+use crate::task_status::Statuses;
+use crate::view::View;
+use crate::viewport::TaskRow;
 use borsh::BorshDeserialize;
 use borsh::BorshSerialize;
-use ratatui::prelude::*;
 
 #[derive(Default, BorshSerialize, BorshDeserialize)]
-pub struct TaskList(Vec<Task>);
+pub struct TaskList {
+    tasks: Vec<Task>,
+    settings: ListSettings,
+    archive: Vec<ArchivedTask>, // oldest first
+}
 
 impl TaskList {
-    pub fn save(self, file: &str) {
-        let mut file = OpenOptions::new().write(true).open(file).unwrap();
-        self.serialize(&mut file).unwrap();
+    pub fn save(&self, file: &str, backup: bool) -> Result<()> {
+        storage::save_atomic(self, file, backup)?;
+        Ok(())
+    }
+
+    pub fn load<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        file_format::read_task_list(reader)
+    }
+
+    pub fn new(tasks: Vec<Task>, settings: ListSettings) -> Self {
+        Self {
+            tasks,
+            settings,
+            archive: vec![],
+        }
+    }
+
+    pub fn with_archive(mut self, archive: Vec<ArchivedTask>) -> Self {
+        self.archive = archive;
+        self
+    }
+
+    pub fn from_tasks(tasks: Vec<Task>) -> Self {
+        Self::new(tasks, ListSettings::default())
+    }
+
+    pub fn settings(&self) -> &ListSettings {
+        &self.settings
+    }
+
+    // the statuses are brought in line by the next derive_statuses
+    pub fn set_parent_status(&mut self, parent_status: ParentStatus) {
+        self.settings.parent_status = parent_status;
+    }
+
+    // with derived parent statuses every task that has children is brought in line with
+    // them, this runs after each change so it doesn't matter where the change came from
+    pub fn derive_statuses(&mut self) {
+        if self.settings.parent_status == ParentStatus::Derived {
+            let statuses = &self.settings.statuses;
+            self.tasks
+                .iter_mut()
+                .for_each(|task| task.derive_status(statuses));
+        }
     }
 
-    pub fn incr_level(&self, task_indexer: &mut TaskIndexer) {
+    // a status set on this task would be put straight back by derive_statuses
+    pub fn status_is_derived(&self, task_indexer: &TaskIndexer) -> bool {
+        self.settings.parent_status == ParentStatus::Derived
+            && self
+                .get_task(task_indexer)
+                .is_some_and(|task| task.child_count() != 0)
+    }
+
+    // like derive_statuses this runs after each change, archived tasks are included for
+    // lists read from older files
+    pub fn stamp_finished(&mut self, now: Option<i64>) {
+        let statuses = &self.settings.statuses;
+        self.tasks
+            .iter_mut()
+            .chain(self.archive.iter_mut().map(|entry| &mut entry.task))
+            .for_each(|task| task.stamp_finished(statuses, now));
+    }
+
+    pub fn statuses(&self) -> &Statuses {
+        &self.settings.statuses
+    }
+
+    pub fn statuses_mut(&mut self) -> &mut Statuses {
+        &mut self.settings.statuses
+    }
+
+    // tasks that had the removed status go back to the initial one
+    pub fn remove_status(&mut self, name: &str) -> Result<()> {
+        let removed = self.settings.statuses.remove(name)?;
+        let initial = self.settings.statuses.initial();
+        self.tasks
+            .iter_mut()
+            .chain(self.archive.iter_mut().map(|entry| &mut entry.task))
+            .for_each(|task| task.replace_status(removed, initial));
+        Ok(())
+    }
+
+    fn task_at(&self, index: usize) -> Result<&Task> {
+        self.tasks.get(index).ok_or(DootError::InvalidSelection)
+    }
+
+    fn task_at_mut(&mut self, index: usize) -> Result<&mut Task> {
+        self.tasks.get_mut(index).ok_or(DootError::InvalidSelection)
+    }
+
+    pub fn incr_level(&self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
             TaskIndexer::Child(selected_child_index, task_indexer) => {
-                self.0[*selected_child_index].incr_level(task_indexer);
+                self.task_at(*selected_child_index)?
+                    .incr_level(task_indexer)?;
             }
             TaskIndexer::SelectedChild(selected_child_index) => {
-                if self.0[*selected_child_index].child_count() != 0 {
+                if self.task_at(*selected_child_index)?.has_visible_children() {
                     *task_indexer = TaskIndexer::Child(
                         *selected_child_index,
                         Box::new(TaskIndexer::SelectedChild(0)),
@@ -28,11 +124,12 @@ impl TaskList {
                 }
             }
             TaskIndexer::Null => (),
-            TaskIndexer::Selected => panic!(),
+            TaskIndexer::Selected => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn decr_level(&self, task_indexer: &mut TaskIndexer) {
+    pub fn decr_level(&self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
             TaskIndexer::Child(child_index, child_task_indexer) => match &**child_task_indexer {
                 TaskIndexer::SelectedChild(_) => {
@@ -40,208 +137,595 @@ impl TaskList {
                     *task_indexer = TaskIndexer::SelectedChild(*child_index);
                 }
                 TaskIndexer::Child(_, _) => {
-                    self.0[*child_index].decr_level(child_task_indexer);
+                    self.task_at(*child_index)?.decr_level(child_task_indexer)?;
+                }
+                TaskIndexer::Null | TaskIndexer::Selected => {
+                    return Err(DootError::InvalidSelection);
                 }
-                TaskIndexer::Null | TaskIndexer::Selected => panic!(),
             },
             TaskIndexer::SelectedChild(_selected_child_index) => (),
             TaskIndexer::Null => (),
-            TaskIndexer::Selected => panic!(),
+            TaskIndexer::Selected => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn delete_task(&mut self, task_indexer: &mut TaskIndexer) {
+    pub fn delete_task(&mut self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
             TaskIndexer::Null | TaskIndexer::Selected => (),
             TaskIndexer::Child(selected_child_index, child_task_indexer) => {
                 match **child_task_indexer {
                     TaskIndexer::Null | TaskIndexer::Selected => (),
-                    TaskIndexer::Child(_, _) => {
-                        self.0[*selected_child_index].delete_task(child_task_indexer)
-                    }
+                    TaskIndexer::Child(_, _) => self
+                        .task_at_mut(*selected_child_index)?
+                        .delete_task(child_task_indexer)?,
                     TaskIndexer::SelectedChild(child_selected_child_index) => {
-                        self.0[*selected_child_index].remove_child(child_selected_child_index);
-                        if self.0[*selected_child_index].child_count() == 0 {
+                        let selected_task = self.task_at_mut(*selected_child_index)?;
+                        selected_task.remove_child(child_selected_child_index)?;
+                        if selected_task.child_count() == 0 {
                             *task_indexer = TaskIndexer::SelectedChild(*selected_child_index);
                         }
                     }
                 }
             }
             TaskIndexer::SelectedChild(selected_child_index) => {
-                self.0.remove(*selected_child_index);
+                if *selected_child_index >= self.tasks.len() {
+                    return Err(DootError::InvalidSelection);
+                }
+                self.tasks.remove(*selected_child_index);
 
-                if self.0.is_empty() {
+                if self.tasks.is_empty() {
                     *task_indexer = TaskIndexer::Null;
-                } else if *selected_child_index == self.0.len() {
+                } else if *selected_child_index == self.tasks.len() {
                     *selected_child_index -= 1;
                 }
             }
         };
+        Ok(())
     }
 
-    pub fn append_task(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) {
+    pub fn append_task(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
-            TaskIndexer::SelectedChild(_) => self.0.push(new_task),
-            TaskIndexer::Child(child_index, child_task_indexer) => {
-                self.0[*child_index].append_task(new_task, child_task_indexer)
-            }
+            TaskIndexer::SelectedChild(_) => self.tasks.push(new_task),
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .task_at_mut(*child_index)?
+                .append_task(new_task, child_task_indexer)?,
             TaskIndexer::Null => {
-                self.0.push(new_task);
+                self.tasks.push(new_task);
                 *task_indexer = TaskIndexer::SelectedChild(0);
             }
-            TaskIndexer::Selected => panic!(),
+            TaskIndexer::Selected => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn prepend_task(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) {
+    pub fn prepend_task(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
-            TaskIndexer::SelectedChild(_) => self.0.insert(0, new_task),
-            TaskIndexer::Child(child_index, child_task_indexer) => {
-                self.0[*child_index].prepend_task(new_task, child_task_indexer)
-            }
+            TaskIndexer::SelectedChild(_) => self.tasks.insert(0, new_task),
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .task_at_mut(*child_index)?
+                .prepend_task(new_task, child_task_indexer)?,
             TaskIndexer::Null => {
-                self.0.push(new_task);
+                self.tasks.push(new_task);
                 *task_indexer = TaskIndexer::SelectedChild(0);
             }
-            TaskIndexer::Selected => panic!(),
+            TaskIndexer::Selected => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn insert_task_above(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) {
+    pub fn insert_task_above(
+        &mut self,
+        new_task: Task,
+        task_indexer: &mut TaskIndexer,
+    ) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(selected_child_index) => {
-                self.0.insert(*selected_child_index, new_task)
-            }
-            TaskIndexer::Child(child_index, child_task_indexer) => {
-                self.0[*child_index].insert_task_above(new_task, child_task_indexer)
+                if *selected_child_index > self.tasks.len() {
+                    return Err(DootError::InvalidSelection);
+                }
+                self.tasks.insert(*selected_child_index, new_task)
             }
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .task_at_mut(*child_index)?
+                .insert_task_above(new_task, child_task_indexer)?,
             TaskIndexer::Null => {
-                self.0.push(new_task);
+                self.tasks.push(new_task);
                 *task_indexer = TaskIndexer::SelectedChild(0);
             }
-            TaskIndexer::Selected => panic!(),
+            TaskIndexer::Selected => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn insert_task_below(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) {
+    pub fn insert_task_below(
+        &mut self,
+        new_task: Task,
+        task_indexer: &mut TaskIndexer,
+    ) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(selected_child_index) => {
-                if *selected_child_index != self.0.len() - 1 {
-                    self.0.insert((*selected_child_index) + 1, new_task)
+                if *selected_child_index + 1 < self.tasks.len() {
+                    self.tasks.insert((*selected_child_index) + 1, new_task)
                 } else {
-                    self.0.push(new_task)
+                    self.tasks.push(new_task)
                 }
             }
-            TaskIndexer::Child(child_index, child_task_indexer) => {
-                self.0[*child_index].insert_task_below(new_task, child_task_indexer)
-            }
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .task_at_mut(*child_index)?
+                .insert_task_below(new_task, child_task_indexer)?,
             TaskIndexer::Null => {
-                self.0.push(new_task);
+                self.tasks.push(new_task);
                 *task_indexer = TaskIndexer::SelectedChild(0);
             }
-            TaskIndexer::Selected => panic!(),
+            TaskIndexer::Selected => return Err(DootError::InvalidSelection),
+        }
+        Ok(())
+    }
+
+    // adds new_task after the selected task's other children and selects it, with nothing
+    // selected it goes at the end of the top level instead
+    pub fn add_child_task(&mut self, new_task: Task, task_indexer: &mut TaskIndexer) -> Result<()> {
+        let mut path = task_indexer.path();
+        path.push(self.sibling_count(&path));
+        self.insert_at(&path, new_task)?;
+        self.expand_ancestors(&path);
+        *task_indexer = TaskIndexer::from_path(&path);
+        Ok(())
+    }
+
+    pub fn get_task(&self, task_indexer: &TaskIndexer) -> Option<&Task> {
+        match task_indexer {
+            TaskIndexer::SelectedChild(selected_child_index) => {
+                self.tasks.get(*selected_child_index)
+            }
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .tasks
+                .get(*child_index)
+                .and_then(|task| task.get_task(child_task_indexer)),
+            TaskIndexer::Null | TaskIndexer::Selected => None,
+        }
+    }
+
+    pub fn get_task_mut(&mut self, task_indexer: &TaskIndexer) -> Option<&mut Task> {
+        match task_indexer {
+            TaskIndexer::SelectedChild(selected_child_index) => {
+                self.tasks.get_mut(*selected_child_index)
+            }
+            TaskIndexer::Child(child_index, child_task_indexer) => self
+                .tasks
+                .get_mut(*child_index)
+                .and_then(|task| task.get_task_mut(child_task_indexer)),
+            TaskIndexer::Null | TaskIndexer::Selected => None,
+        }
+    }
+
+    // number of tasks directly under the task at parent_path, or at the top level
+    pub fn sibling_count(&self, parent_path: &[usize]) -> usize {
+        match parent_path {
+            [] => self.tasks.len(),
+            _ => self
+                .get_task(&TaskIndexer::from_path(parent_path))
+                .map(|parent| parent.child_count())
+                .unwrap_or(0),
+        }
+    }
+
+    pub fn insert_at(&mut self, path: &[usize], new_task: Task) -> Result<()> {
+        match path {
+            [] => Err(DootError::InvalidSelection),
+            [index] if *index <= self.tasks.len() => {
+                self.tasks.insert(*index, new_task);
+                Ok(())
+            }
+            [_] => Err(DootError::InvalidSelection),
+            [parent_path @ .., index] => self
+                .get_task_mut(&TaskIndexer::from_path(parent_path))
+                .ok_or(DootError::InvalidSelection)?
+                .insert_child(*index, new_task),
+        }
+    }
+
+    pub fn remove_at(&mut self, path: &[usize]) -> Result<Task> {
+        match path {
+            [] => Err(DootError::InvalidSelection),
+            [index] if *index < self.tasks.len() => Ok(self.tasks.remove(*index)),
+            [_] => Err(DootError::InvalidSelection),
+            [parent_path @ .., index] => self
+                .get_task_mut(&TaskIndexer::from_path(parent_path))
+                .ok_or(DootError::InvalidSelection)?
+                .remove_child(*index),
+        }
+    }
+
+    pub fn archive(&self) -> &[ArchivedTask] {
+        &self.archive
+    }
+
+    // paths of the tasks that are finished along with everything under them, a finished
+    // task with unfinished subtasks stays but its finished subtasks are still picked out
+    pub fn finished_paths(&self) -> Vec<Vec<usize>> {
+        let mut paths = vec![];
+        self.tasks
+            .iter()
+            .enumerate()
+            .for_each(|(i, task)| task.push_finished_paths(vec![i], self.statuses(), &mut paths));
+        paths
+    }
+
+    // archives every finished task, calling on_archive with the path, archive index and entry
+    // of each one as it goes so the caller can record them. returns the paths, first one first
+    pub fn archive_finished(
+        &mut self,
+        mut on_archive: impl FnMut(&[usize], usize, &ArchivedTask),
+    ) -> Result<Vec<Vec<usize>>> {
+        let paths = self.finished_paths();
+        // from the bottom up so the paths still to go keep pointing at the same tasks
+        for path in paths.iter().rev() {
+            let index = self.archive.len();
+            let entry = self.archive_entry(path)?;
+            self.archive_at(path, index, entry.clone())?;
+            on_archive(path, index, &entry);
         }
+        Ok(paths)
+    }
+
+    // archive index of the entry at position when counting from the newest, which is last
+    pub fn archive_index_from_newest(&self, position: usize) -> Option<usize> {
+        self.archive.len().checked_sub(position + 1)
+    }
+
+    // the archive entry the task at path would become, stamped with the current time
+    pub fn archive_entry(&self, path: &[usize]) -> Result<ArchivedTask> {
+        let task = self
+            .get_task(&TaskIndexer::from_path(path))
+            .ok_or(DootError::InvalidSelection)?;
+        let parent_titles = (1..path.len())
+            .filter_map(|depth| self.get_task(&TaskIndexer::from_path(&path[..depth])))
+            .map(|parent| parent.title().to_string())
+            .collect();
+        Ok(ArchivedTask::new(
+            task.clone(),
+            path.to_vec(),
+            parent_titles,
+        ))
+    }
+
+    // takes the task at path out of the list and puts entry in the archive at index
+    pub fn archive_at(&mut self, path: &[usize], index: usize, entry: ArchivedTask) -> Result<()> {
+        if index > self.archive.len() {
+            return Err(DootError::InvalidSelection);
+        }
+        self.remove_at(path)?;
+        self.archive.insert(index, entry);
+        Ok(())
+    }
+
+    // the archive entry goes back to path, which has to be a valid place for it
+    pub fn restore_at(&mut self, index: usize, path: &[usize]) -> Result<()> {
+        let entry = self.archive.get(index).ok_or(DootError::InvalidSelection)?;
+        self.insert_at(path, entry.task.clone())?;
+        self.archive.remove(index);
+        self.expand_ancestors(path);
+        Ok(())
+    }
+
+    // where an archived task would go back to: its old place when the tasks above it are
+    // still there, or the end of the list when they've moved or gone
+    pub fn restore_path(&self, index: usize) -> Option<Vec<usize>> {
+        let entry = self.archive.get(index)?;
+        let (&old_index, parent_path) = entry.path.split_last()?;
+        let parents_match = entry.parent_titles.len() == parent_path.len()
+            && (1..=parent_path.len()).all(|depth| {
+                self.get_task(&TaskIndexer::from_path(&parent_path[..depth]))
+                    .is_some_and(|parent| parent.title() == entry.parent_titles[depth - 1])
+            });
+        let mut path = match parents_match {
+            true => parent_path.to_vec(),
+            false => vec![],
+        };
+        path.push(old_index.min(self.sibling_count(&path)));
+        Some(path)
+    }
+
+    pub fn move_path(&mut self, from: &[usize], to: &[usize]) -> Result<()> {
+        let task = self.remove_at(from)?;
+        if let Err(e) = self.insert_at(to, task.clone()) {
+            self.insert_at(from, task)?;
+            return Err(e);
+        }
+        self.expand_ancestors(to);
+        Ok(())
+    }
+
+    // moves the selected task and points the indexer at where it ended up, the indexer is
+    // left alone when the task can't move any further that way
+    pub fn move_task(&mut self, task_indexer: &mut TaskIndexer, movement: Movement) -> Result<()> {
+        let from = task_indexer.path();
+        let Some((&index, parent_path)) = from.split_last() else {
+            return Ok(());
+        };
+        let mut to = parent_path.to_vec();
+        match movement {
+            Movement::Up if index > 0 => to.push(index - 1),
+            Movement::Down if index + 1 < self.sibling_count(parent_path) => to.push(index + 1),
+            Movement::Indent if index > 0 => {
+                to.push(index - 1);
+                let new_sibling_index = self
+                    .get_task(&TaskIndexer::from_path(&to))
+                    .ok_or(DootError::InvalidSelection)?
+                    .child_count();
+                to.push(new_sibling_index);
+            }
+            Movement::Outdent if !parent_path.is_empty() => {
+                to.pop();
+                to.push(parent_path[parent_path.len() - 1] + 1);
+            }
+            _ => return Ok(()),
+        }
+        self.move_path(&from, &to)?;
+        *task_indexer = TaskIndexer::from_path(&to);
+        Ok(())
+    }
+
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    pub fn edit_task(&mut self, edited_task: Task, task_indexer: &TaskIndexer) -> Result<()> {
+        self.get_task_mut(task_indexer)
+            .ok_or(DootError::InvalidSelection)?
+            .update(edited_task);
+        Ok(())
     }
 
     pub fn default_bytes() -> Vec<u8> {
         let mut buf = Vec::new();
         let default_list = Self::default();
-        let _ = default_list.serialize(&mut buf);
+        let _ = file_format::write_task_list(&default_list, &mut buf);
         buf
     }
-    pub fn draw(&self, frame: &mut Frame, paintable_area: Rect, selected_task: &TaskIndexer) {
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                self.counts_vec()
-                    .into_iter()
-                    .map(|i| Constraint::Length(i * 4))
-                    .collect::<Vec<Constraint>>(),
-            )
-            .split(paintable_area);
-
-        match selected_task {
-            TaskIndexer::Selected => panic!(),
-            TaskIndexer::SelectedChild(selected_child_index) => {
-                self.0.iter().zip(layout.iter()).enumerate().for_each(
-                    |(i, (task, task_layout))| {
-                        if i != *selected_child_index {
-                            task.draw(frame, task_layout, 0, &TaskIndexer::Null);
-                        } else {
-                            task.draw(frame, task_layout, 0, &TaskIndexer::Selected);
-                        }
-                    },
-                );
-            }
-            TaskIndexer::Child(selected_child, child_task_indexer) => self
-                .0
-                .iter()
-                .zip(layout.iter())
-                .enumerate()
-                .for_each(|(i, (task, task_layout))| {
-                    if i != *selected_child {
-                        task.draw(frame, task_layout, 0, &TaskIndexer::Null);
-                    } else {
-                        task.draw(frame, task_layout, 0, child_task_indexer);
-                    }
-                }),
-            TaskIndexer::Null => {
-                self.0
-                    .iter()
-                    .zip(layout.iter())
-                    .for_each(|(task, task_layout)| {
-                        task.draw(frame, task_layout, 0, &TaskIndexer::Null);
-                    })
-            }
+    pub fn rows(&self, selected_task: &TaskIndexer, view: &View) -> Vec<TaskRow<'_>> {
+        let selected_path = selected_task.path();
+        let mut rows = vec![];
+        view.order(&self.tasks, self.statuses())
+            .into_iter()
+            .for_each(|i| {
+                self.tasks[i].push_rows(vec![i], &selected_path, &mut rows, view, self.statuses());
+            });
+        rows
+    }
+
+    // the path of every task the view shows, collapsed or not, in the order they're drawn
+    fn paths(&self, view: &View) -> Vec<Vec<usize>> {
+        let mut paths = vec![];
+        view.order(&self.tasks, self.statuses())
+            .into_iter()
+            .for_each(|i| self.tasks[i].push_paths(vec![i], &mut paths, view, self.statuses()));
+        paths
+    }
+
+    pub fn count_matches(&self, query: &str, view: &View) -> usize {
+        self.paths(view)
+            .iter()
+            .filter_map(|path| self.get_task(&TaskIndexer::from_path(path)))
+            .filter(|task| task.matches_search(query))
+            .count()
+    }
+
+    // the path of the first task matching query after the selected one (or before it when
+    // not forward), wrapping around. the selected task itself only counts when it isn't
+    // skipped, and comes last when it is
+    pub fn find_match(
+        &self,
+        selected_task: &TaskIndexer,
+        query: &str,
+        view: &View,
+        forward: bool,
+        skip_selected: bool,
+    ) -> Option<Vec<usize>> {
+        let paths = self.paths(view);
+        let count = paths.len();
+        let selected_path = selected_task.path();
+        let start = paths.iter().position(|path| *path == selected_path);
+        let first = skip_selected as usize;
+        (first..first + count)
+            .map(|step| match (start, forward) {
+                (Some(start), true) => (start + step) % count,
+                (Some(start), false) => (start + count * 2 - step) % count,
+                (None, true) => step - first,
+                (None, false) => count - 1 - (step - first),
+            })
+            .map(|i| &paths[i])
+            .find(|path| {
+                self.get_task(&TaskIndexer::from_path(path))
+                    .is_some_and(|task| task.matches_search(query))
+            })
+            .cloned()
+    }
+
+    // every tag used in the list, in the order they first appear
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut found = vec![];
+        self.tasks
+            .iter()
+            .for_each(|task| task.collect_tags(&mut found));
+        found
+    }
+
+    // the tasks directly under the task at parent_path, or the top level
+    fn siblings(&self, parent_path: &[usize]) -> &[Task] {
+        match parent_path {
+            [] => &self.tasks,
+            _ => self
+                .get_task(&TaskIndexer::from_path(parent_path))
+                .map_or(&[], |parent| parent.children()),
+        }
+    }
+
+    // selects the sibling shown above (or below when forward) the selected task, wrapping
+    // around like incr and decr but following the view's order and skipping hidden tasks
+    pub fn step(&self, task_indexer: &mut TaskIndexer, view: &View, forward: bool) {
+        let mut path = task_indexer.path();
+        let Some(index) = path.pop() else {
+            return;
+        };
+        let order = view.order(self.siblings(&path), self.statuses());
+        let Some(position) = order.iter().position(|&i| i == index) else {
+            return;
         };
+        let position = match forward {
+            true => (position + 1) % order.len(),
+            false => (position + order.len() - 1) % order.len(),
+        };
+        path.push(order[position]);
+        *task_indexer = TaskIndexer::from_path(&path);
     }
 
-    pub fn counts_vec(&self) -> Vec<u16> {
-        self.0.iter().map(|t| t.count()).collect()
+    // like incr_level but lands on the first child the view shows
+    pub fn step_in(&self, task_indexer: &mut TaskIndexer, view: &View) {
+        let mut path = task_indexer.path();
+        let Some(task) = self.get_task(task_indexer) else {
+            return;
+        };
+        if task.is_collapsed() {
+            return;
+        }
+        if let Some(&first) = view.order(task.children(), self.statuses()).first() {
+            path.push(first);
+            *task_indexer = TaskIndexer::from_path(&path);
+        }
+    }
+
+    // where the selection should go when the view hides the selected task: the nearest
+    // shown sibling of the deepest shown task above it, or that task itself. None when the
+    // selection can stay where it is
+    pub fn visible_selection(
+        &self,
+        task_indexer: &TaskIndexer,
+        view: &View,
+    ) -> Option<TaskIndexer> {
+        let path = task_indexer.path();
+        let shown_depth = (1..=path.len())
+            .take_while(|&depth| {
+                self.get_task(&TaskIndexer::from_path(&path[..depth]))
+                    .is_some_and(|task| view.shows(task, self.statuses()))
+            })
+            .count();
+        if shown_depth == path.len() && !path.is_empty() {
+            return None;
+        }
+        let mut new_path = path[..shown_depth].to_vec();
+        let hidden_index = path.get(shown_depth).copied().unwrap_or(0);
+        let order = view.order(self.siblings(&new_path), self.statuses());
+        let below = order.iter().copied().filter(|&i| i >= hidden_index).min();
+        let above = order.iter().copied().filter(|&i| i < hidden_index).max();
+        if let Some(index) = below.or(above) {
+            new_path.push(index);
+        }
+        (new_path != path).then(|| TaskIndexer::from_path(&new_path))
+    }
+
+    // makes sure the task at path is drawn by expanding every task above it
+    pub fn expand_ancestors(&mut self, path: &[usize]) {
+        (1..path.len()).for_each(|depth| {
+            if let Some(ancestor) = self.get_task_mut(&TaskIndexer::from_path(&path[..depth])) {
+                ancestor.set_collapsed(false);
+            }
+        });
+    }
+
+    // tasks at depth 1 (the top level) to depth - 1 are expanded, everything deeper is collapsed
+    pub fn expand_to_depth(&mut self, depth: usize) {
+        self.tasks
+            .iter_mut()
+            .for_each(|task| task.expand_to_depth(depth.saturating_sub(1)));
+    }
+
+    pub fn task_count(&self) -> usize {
+        self.tasks.iter().map(|task| task.count() as usize).sum()
     }
 
     pub fn first_task(&self) -> TaskIndexer {
-        if !self.0.is_empty() {
+        if !self.tasks.is_empty() {
             TaskIndexer::SelectedChild(0)
         } else {
             TaskIndexer::Null
         }
     }
 
-    pub fn decr(&mut self, task_indexer: &mut TaskIndexer) {
+    pub fn decr(&mut self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(selected_child_index) => {
-                *selected_child_index = (*selected_child_index + 1) % self.0.len();
+                if self.tasks.is_empty() {
+                    return Err(DootError::InvalidSelection);
+                }
+                *selected_child_index = (*selected_child_index + 1) % self.tasks.len();
             }
             TaskIndexer::Child(selected_child_index, task_indexer) => {
-                self.0[*selected_child_index].decr(task_indexer);
+                self.task_at_mut(*selected_child_index)?
+                    .decr(task_indexer)?;
             }
             TaskIndexer::Null => (),
-            TaskIndexer::Selected => todo!(),
+            TaskIndexer::Selected => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 
-    pub fn incr(&mut self, task_indexer: &mut TaskIndexer) {
+    pub fn incr(&mut self, task_indexer: &mut TaskIndexer) -> Result<()> {
         match task_indexer {
             TaskIndexer::SelectedChild(selected_child_index) => {
                 if *selected_child_index != 0 {
                     *selected_child_index -= 1;
-                } else if !self.0.is_empty() {
-                    *selected_child_index = self.0.len() - 1;
+                } else if !self.tasks.is_empty() {
+                    *selected_child_index = self.tasks.len() - 1;
                 }
             }
             TaskIndexer::Child(selected_child_index, task_indexer) => {
-                self.0[*selected_child_index].incr(task_indexer);
+                self.task_at_mut(*selected_child_index)?
+                    .incr(task_indexer)?;
             }
             TaskIndexer::Null => (),
-            TaskIndexer::Selected => todo!(),
+            TaskIndexer::Selected => return Err(DootError::InvalidSelection),
         }
+        Ok(())
     }
 }
 
+pub enum Movement {
+    Up,
+    Down,
+    Indent,  // becomes the last child of the task above it
+    Outdent, // becomes the sibling right after its parent
+}
+
 pub enum TaskIndexer {
     SelectedChild(usize), // the level this is matched means it's child is selected
     Child(usize, Box<TaskIndexer>), // this will pull as which to pass the child indexer to
     Selected,             // whatever task gets passed this is the one that is selected
     Null,
 }
+
+impl TaskIndexer {
+    // the list of child indices leading to the selected task, empty when nothing is selected
+    pub fn path(&self) -> Vec<usize> {
+        match self {
+            Self::SelectedChild(index) => vec![*index],
+            Self::Child(index, child_task_indexer) => {
+                let mut path = vec![*index];
+                path.extend(child_task_indexer.path());
+                path
+            }
+            Self::Selected | Self::Null => vec![],
+        }
+    }
+
+    // builds the indexer that selects the task at the given list of child indices
+    pub fn from_path(path: &[usize]) -> Self {
+        match path {
+            [] => Self::Null,
+            [index] => Self::SelectedChild(*index),
+            [index, rest @ ..] => Self::Child(*index, Box::new(Self::from_path(rest))),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::Task;
+use crate::TaskList;
+use crate::priority::Priority;
+use crate::task_date::TaskDate;
+use crate::task_status::Statuses;
+use colored::Colorize;
+use std::io::{IsTerminal, Write};
+use tabled::settings::Style;
+use tabled::{Table, Tabled};
+
+#[derive(Tabled)]
+struct TaskRow {
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Title")]
+    title: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "Priority")]
+    priority: String,
+    #[tabled(rename = "Progress")]
+    progress: String,
+    #[tabled(rename = "Due")]
+    due: String,
+    #[tabled(rename = "Tags")]
+    tags: String,
+    #[tabled(rename = "Description")]
+    desc: String,
+}
+
+type TaskFilter = Box<dyn Fn(&Task) -> bool>;
+
+pub struct TaskPrinter {
+    plain: bool,
+    filter: Option<TaskFilter>,
+    today: TaskDate,
+}
+
+impl TaskPrinter {
+    // plain output has no colors or box drawing, colors are also dropped when stdout isn't a terminal
+    pub fn new(plain: bool) -> Self {
+        let plain = plain || !std::io::stdout().is_terminal();
+        colored::control::set_override(!plain);
+        Self {
+            plain,
+            filter: None,
+            today: TaskDate::today(),
+        }
+    }
+
+    // only tasks the filter matches are printed, along with the tasks above them. with more
+    // than one filter a task has to match all of them
+    pub fn with_filter(mut self, filter: impl Fn(&Task) -> bool + 'static) -> Self {
+        self.filter = Some(match self.filter.take() {
+            None => Box::new(filter),
+            Some(previous) => Box::new(move |task| previous(task) && filter(task)),
+        });
+        self
+    }
+
+    fn shows(&self, task: &Task) -> bool {
+        match &self.filter {
+            None => true,
+            Some(filter) => filter(task) || task.children().iter().any(|child| self.shows(child)),
+        }
+    }
+
+    pub fn print_tree(&self, tasks: &TaskList) -> std::io::Result<()> {
+        let mut lines = vec![];
+        self.tree_lines(tasks.statuses(), tasks.tasks(), "", "", &mut lines);
+        let mut stdout = std::io::stdout().lock();
+        lines.iter().try_for_each(|line| writeln!(stdout, "{line}"))
+    }
+
+    pub fn print_table(&self, tasks: &TaskList) -> std::io::Result<()> {
+        let mut rows = vec![];
+        self.table_rows(tasks.statuses(), tasks.tasks(), "", &mut rows);
+        let mut table = Table::new(rows);
+        match self.plain {
+            true => table.with(Style::blank()),
+            false => table.with(Style::rounded()),
+        };
+        writeln!(std::io::stdout().lock(), "{table}")
+    }
+
+    fn tree_lines(
+        &self,
+        statuses: &Statuses,
+        tasks: &[Task],
+        parent_path: &str,
+        prefix: &str,
+        lines: &mut Vec<String>,
+    ) {
+        // paths keep the task's real index even when the tasks around it are filtered out
+        let shown: Vec<(usize, &Task)> = tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| self.shows(task))
+            .collect();
+        shown
+            .iter()
+            .enumerate()
+            .for_each(|(shown_index, (i, task))| {
+                let path = format!("{parent_path}{}", i + 1);
+                let is_last = shown_index == shown.len() - 1;
+                let (branch, child_prefix) = match (self.plain, parent_path.is_empty(), is_last) {
+                    (_, true, _) => ("", String::new()),
+                    (true, false, _) => ("  ", format!("{prefix}  ")),
+                    (false, false, false) => ("├─ ", format!("{prefix}│  ")),
+                    (false, false, true) => ("└─ ", format!("{prefix}   ")),
+                };
+                let mut line = format!(
+                    "{prefix}{branch}{} {} [{}]",
+                    path.bold(),
+                    task.title(),
+                    statuses.colored_name(task.status())
+                );
+                // tags written in the title are already printed with it
+                let extra_tags = task.extra_tags();
+                if !extra_tags.is_empty() {
+                    line.push_str(&format!(" {}", extra_tags.join(" ").magenta()));
+                }
+                if task.priority() != Priority::None {
+                    line.push_str(&format!(" !{}", task.priority().name()));
+                }
+                if let Some((done, total)) = task.progress(statuses) {
+                    line.push_str(&format!(" {done}/{total}"));
+                }
+                if let Some(due) = self.due_label(task, statuses) {
+                    line.push_str(&format!(" {due}"));
+                }
+                if !task.desc().is_empty() {
+                    // one line per task, the description's own lines are run together
+                    let desc = task.desc().lines().collect::<Vec<_>>().join(" / ");
+                    line.push_str(&format!(" {}", desc.dimmed()));
+                }
+                lines.push(line);
+                self.tree_lines(
+                    statuses,
+                    task.children(),
+                    &format!("{path}."),
+                    &child_prefix,
+                    lines,
+                );
+            });
+    }
+
+    fn table_rows(
+        &self,
+        statuses: &Statuses,
+        tasks: &[Task],
+        parent_path: &str,
+        rows: &mut Vec<TaskRow>,
+    ) {
+        tasks.iter().enumerate().for_each(|(i, task)| {
+            if !self.shows(task) {
+                return;
+            }
+            let path = format!("{parent_path}{}", i + 1);
+            rows.push(TaskRow {
+                path: path.clone(),
+                title: task.title().to_string(),
+                status: statuses.colored_name(task.status()).to_string(),
+                priority: match task.priority() {
+                    Priority::None => String::new(),
+                    priority => priority.name().to_string(),
+                },
+                progress: task
+                    .progress(statuses)
+                    .map(|(done, total)| format!("{done}/{total}"))
+                    .unwrap_or_default(),
+                due: self
+                    .due_label(task, statuses)
+                    .map(|due| due.to_string())
+                    .unwrap_or_default(),
+                tags: task.tags().join(" "),
+                desc: task.desc().to_string(),
+            });
+            self.table_rows(statuses, task.children(), &format!("{path}."), rows);
+        });
+    }
+
+    // red when overdue and yellow on the day, the same as the terminal interface
+    fn due_label(&self, task: &Task, statuses: &Statuses) -> Option<colored::ColoredString> {
+        let due = task.due()?;
+        let label = format!("due {}", due.describe(self.today));
+        Some(match task.is_due_by(self.today, statuses) {
+            true if due < self.today => label.color(colored::Color::Red).bold(),
+            true => label.color(colored::Color::Yellow).bold(),
+            false => label.normal(),
+        })
+    }
+}
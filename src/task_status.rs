@@ -1,56 +1,376 @@
+use crate::error::{DootError, Result};
 use borsh::BorshDeserialize;
 use borsh::BorshSerialize;
+use colored::{ColoredString, Colorize};
 use ratatui::prelude::*;
 use ratatui::widgets::Paragraph;
 
-#[derive(Default, BorshSerialize, BorshDeserialize, Clone)]
-pub enum TaskStatus {
-    Complete,
-    InProgress,
-    #[default]
-    NotStarted,
-}
+// a task's status is the id of one of the list's status definitions, ids don't change when a
+// status is renamed or reordered, the built in ones keep the values the old fixed statuses
+// were saved as so older files read the same
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq)]
+pub struct TaskStatus(u8);
 
 impl TaskStatus {
-    pub fn to_line(&self) -> Line {
+    const COMPLETE: Self = Self(0);
+    const IN_PROGRESS: Self = Self(1);
+    const NOT_STARTED: Self = Self(2);
+}
+
+impl Default for TaskStatus {
+    fn default() -> Self {
+        Self::NOT_STARTED
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq)]
+pub enum StatusColor {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+    DarkGray,
+    White,
+}
+
+impl StatusColor {
+    const ALL: [Self; 9] = [
+        Self::Red,
+        Self::Green,
+        Self::Yellow,
+        Self::Blue,
+        Self::Magenta,
+        Self::Cyan,
+        Self::Gray,
+        Self::DarkGray,
+        Self::White,
+    ];
+
+    pub fn name(&self) -> &'static str {
         match self {
-            Self::Complete => Line::from("Complete").right_aligned().style(Color::Green),
-            Self::InProgress => Line::from("InProgress").right_aligned().style(Color::Blue),
-            Self::NotStarted => Line::from("NotStarted").right_aligned().style(Color::Red),
+            Self::Red => "red",
+            Self::Green => "green",
+            Self::Yellow => "yellow",
+            Self::Blue => "blue",
+            Self::Magenta => "magenta",
+            Self::Cyan => "cyan",
+            Self::Gray => "gray",
+            Self::DarkGray => "darkgray",
+            Self::White => "white",
         }
     }
 
-    pub fn as_paragraph(&self) -> Paragraph {
+    pub fn parse(value: &str) -> std::result::Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|color| color.name() == value.to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|color| color.name()).collect();
+                format!("expected one of {}, got {value}", names.join(", "))
+            })
+    }
+
+    fn color(&self) -> Color {
         match self {
-            Self::Complete => Paragraph::new("Complete").style(Style::new().green()),
-            Self::InProgress => Paragraph::new("InProgress").style(Style::new().blue()),
-            Self::NotStarted => Paragraph::new("NotStarted").style(Style::new().red()),
+            Self::Red => Color::Red,
+            Self::Green => Color::Green,
+            Self::Yellow => Color::Yellow,
+            Self::Blue => Color::Blue,
+            Self::Magenta => Color::Magenta,
+            Self::Cyan => Color::Cyan,
+            Self::Gray => Color::Gray,
+            Self::DarkGray => Color::DarkGray,
+            Self::White => Color::White,
         }
     }
 
-    pub fn as_paragraph_selected(&self) -> Paragraph {
+    // the brighter version used for the selected status in the task form
+    fn light_color(&self) -> Color {
         match self {
-            Self::Complete => Paragraph::new("Complete").style(Style::new().light_green().bold()),
-            Self::InProgress => {
-                Paragraph::new("InProgress").style(Style::new().light_blue().bold())
-            }
-            Self::NotStarted => Paragraph::new("NotStarted").style(Style::new().light_red().bold()),
+            Self::Red => Color::LightRed,
+            Self::Green => Color::LightGreen,
+            Self::Yellow => Color::LightYellow,
+            Self::Blue => Color::LightBlue,
+            Self::Magenta => Color::LightMagenta,
+            Self::Cyan => Color::LightCyan,
+            Self::Gray | Self::White => Color::White,
+            Self::DarkGray => Color::Gray,
         }
     }
 
-    pub fn next_status(&mut self) {
-        *self = match self {
-            TaskStatus::Complete => TaskStatus::NotStarted,
-            TaskStatus::InProgress => TaskStatus::Complete,
-            TaskStatus::NotStarted => TaskStatus::InProgress,
+    // ratatui's Stylize also has green()/blue()/red() on &str, so colored's color() is used
+    fn terminal_color(&self) -> colored::Color {
+        match self {
+            Self::Red => colored::Color::Red,
+            Self::Green => colored::Color::Green,
+            Self::Yellow => colored::Color::Yellow,
+            Self::Blue => colored::Color::Blue,
+            Self::Magenta => colored::Color::Magenta,
+            Self::Cyan => colored::Color::Cyan,
+            Self::Gray => colored::Color::White,
+            Self::DarkGray => colored::Color::BrightBlack,
+            Self::White => colored::Color::BrightWhite,
         }
     }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct StatusDef {
+    pub id: TaskStatus,
+    pub name: String,
+    pub color: StatusColor,
+    pub done: bool, // counts towards a parent's progress and completes it when derived
+}
+
+// the statuses a list can use, in the order space cycles through them, the first one is what
+// new tasks start as
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct Statuses {
+    defs: Vec<StatusDef>,
+    next_id: u16, // ids aren't reused so nothing holding a removed one picks up a new status
+}
+
+impl Default for Statuses {
+    fn default() -> Self {
+        Self::from_defs(vec![
+            StatusDef {
+                id: TaskStatus::NOT_STARTED,
+                name: "NotStarted".to_string(),
+                color: StatusColor::Red,
+                done: false,
+            },
+            StatusDef {
+                id: TaskStatus::IN_PROGRESS,
+                name: "InProgress".to_string(),
+                color: StatusColor::Blue,
+                done: false,
+            },
+            StatusDef {
+                id: TaskStatus::COMPLETE,
+                name: "Complete".to_string(),
+                color: StatusColor::Green,
+                done: true,
+            },
+        ])
+    }
+}
+
+impl Statuses {
+    // ids carry on after the highest one in defs
+    pub fn from_defs(defs: Vec<StatusDef>) -> Self {
+        let next_id = defs
+            .iter()
+            .map(|def| def.id.0 as u16 + 1)
+            .max()
+            .unwrap_or(0);
+        Self { defs, next_id }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusDef> {
+        self.defs.iter()
+    }
+
+    fn get(&self, status: TaskStatus) -> Option<&StatusDef> {
+        self.defs.iter().find(|def| def.id == status)
+    }
+
+    fn position(&self, status: TaskStatus) -> Option<usize> {
+        self.defs.iter().position(|def| def.id == status)
+    }
+
+    // where a status comes in the cycle order, for sorting
+    pub fn order(&self, status: TaskStatus) -> usize {
+        self.position(status).unwrap_or(usize::MAX)
+    }
+
+    pub fn find(&self, name: &str) -> Result<TaskStatus> {
+        self.defs
+            .iter()
+            .find(|def| def.name.eq_ignore_ascii_case(name))
+            .map(|def| def.id)
+            .ok_or_else(|| DootError::UnknownStatus(name.to_string()))
+    }
+
+    pub fn name(&self, status: TaskStatus) -> &str {
+        self.get(status).map_or("Unknown", |def| &def.name)
+    }
+
+    fn color(&self, status: TaskStatus) -> StatusColor {
+        self.get(status).map_or(StatusColor::Gray, |def| def.color)
+    }
+
+    pub fn is_done(&self, status: TaskStatus) -> bool {
+        self.get(status).is_some_and(|def| def.done)
+    }
+
+    pub fn initial(&self) -> TaskStatus {
+        self.defs
+            .first()
+            .map_or_else(TaskStatus::default, |def| def.id)
+    }
+
+    pub fn first_done(&self) -> TaskStatus {
+        self.defs
+            .iter()
+            .find(|def| def.done)
+            .map_or_else(|| self.initial(), |def| def.id)
+    }
 
-    pub fn prev_status(&mut self) {
-        *self = match self {
-            TaskStatus::Complete => TaskStatus::InProgress,
-            TaskStatus::InProgress => TaskStatus::NotStarted,
-            TaskStatus::NotStarted => TaskStatus::Complete,
+    // what a started parent becomes when none of its children are somewhere in between, the
+    // built in InProgress while it's still around, or else the first status after the
+    // initial one that isn't done
+    fn first_started(&self) -> TaskStatus {
+        if self
+            .get(TaskStatus::IN_PROGRESS)
+            .is_some_and(|def| !def.done && def.id != self.initial())
+        {
+            return TaskStatus::IN_PROGRESS;
         }
+        self.defs
+            .iter()
+            .skip(1)
+            .find(|def| !def.done)
+            .map_or_else(|| self.initial(), |def| def.id)
+    }
+
+    pub fn next(&self, status: TaskStatus) -> TaskStatus {
+        match self.position(status) {
+            Some(i) => self.defs[(i + 1) % self.defs.len()].id,
+            None => self.initial(),
+        }
+    }
+
+    pub fn prev(&self, status: TaskStatus) -> TaskStatus {
+        match self.position(status) {
+            Some(i) => self.defs[(i + self.defs.len() - 1) % self.defs.len()].id,
+            None => self.initial(),
+        }
+    }
+
+    // the status a parent gets from its children's, done once they all are (keeping their
+    // status when they agree), otherwise the earliest status a child has that's neither the
+    // initial one nor done, so a blocked child shows up on its parent
+    pub fn derived(&self, children: &[TaskStatus]) -> TaskStatus {
+        if children.iter().all(|&status| self.is_done(status)) {
+            return match children {
+                [first, rest @ ..] if rest.iter().all(|status| status == first) => *first,
+                _ => self.first_done(),
+            };
+        }
+        if children.iter().all(|&status| status == self.initial()) {
+            return self.initial();
+        }
+        self.defs
+            .iter()
+            .skip(1)
+            .find(|def| !def.done && children.contains(&def.id))
+            .map_or_else(|| self.first_started(), |def| def.id)
+    }
+
+    pub fn add(&mut self, name: &str, color: StatusColor, done: bool) -> Result<()> {
+        if self.find(name).is_ok() {
+            return Err(DootError::DuplicateStatus(name.to_string()));
+        }
+        let id = u8::try_from(self.next_id)
+            .map(TaskStatus)
+            .map_err(|_| DootError::TooManyStatuses)?;
+        self.next_id += 1;
+        self.defs.push(StatusDef {
+            id,
+            name: name.to_string(),
+            color,
+            done,
+        });
+        Ok(())
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut StatusDef> {
+        let status = self.find(name)?;
+        Ok(self.defs.iter_mut().find(|def| def.id == status).unwrap())
+    }
+
+    // names are matched ignoring case, so a status can be renamed to a different case of itself
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<()> {
+        let status = self.find(name)?;
+        if self.find(new_name).is_ok_and(|other| other != status) {
+            return Err(DootError::DuplicateStatus(new_name.to_string()));
+        }
+        self.get_mut(name)?.name = new_name.to_string();
+        Ok(())
+    }
+
+    // moves a status to position (zero based) in the cycle order
+    pub fn reorder(&mut self, name: &str, position: usize) -> Result<()> {
+        let from = self.position(self.find(name)?).unwrap();
+        let def = self.defs.remove(from);
+        self.defs.insert(position.min(self.defs.len()), def);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<TaskStatus> {
+        let status = self.find(name)?;
+        if self.defs.len() == 1 {
+            return Err(DootError::LastStatus);
+        }
+        self.defs.retain(|def| def.id != status);
+        Ok(status)
+    }
+
+    pub fn colored_name(&self, status: TaskStatus) -> ColoredString {
+        self.name(status).color(self.color(status).terminal_color())
+    }
+
+    pub fn to_span(&self, status: TaskStatus) -> Span<'_> {
+        Span::from(self.name(status)).style(self.color(status).color())
+    }
+
+    pub fn to_line(&self, status: TaskStatus) -> Line<'_> {
+        Line::from(self.name(status))
+            .right_aligned()
+            .style(self.color(status).color())
+    }
+
+    pub fn as_paragraph(&self, status: TaskStatus) -> Paragraph<'_> {
+        Paragraph::new(self.name(status)).style(Style::new().fg(self.color(status).color()))
+    }
+
+    pub fn as_paragraph_selected(&self, status: TaskStatus) -> Paragraph<'_> {
+        Paragraph::new(self.name(status))
+            .style(Style::new().fg(self.color(status).light_color()).bold())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removed_ids_are_not_given_out_again() {
+        let mut statuses = Statuses::default();
+        statuses.add("Shipped", StatusColor::Green, true).unwrap();
+        let shipped = statuses.find("Shipped").unwrap();
+        statuses.remove("Shipped").unwrap();
+        statuses.add("Dropped", StatusColor::Gray, false).unwrap();
+        assert!(statuses.find("Dropped").unwrap() != shipped);
+        assert_eq!(statuses.name(shipped), "Unknown");
+    }
+
+    #[test]
+    fn renaming_only_clashes_with_other_statuses() {
+        let mut statuses = Statuses::default();
+        statuses.rename("complete", "Done").unwrap();
+        statuses.rename("Done", "done").unwrap();
+        assert_eq!(statuses.name(statuses.first_done()), "done");
+        assert!(statuses.rename("done", "notstarted").is_err());
+    }
+
+    #[test]
+    fn ids_carry_on_after_the_highest_loaded_one() {
+        let mut statuses = Statuses::from_defs(Statuses::default().defs);
+        statuses.add("Waiting", StatusColor::Yellow, false).unwrap();
+        assert_eq!(statuses.find("Waiting").unwrap().0, 3);
     }
 }
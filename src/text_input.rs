@@ -1,83 +1,476 @@
+use crate::wrap;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// the character a key press types, if any. plain ctrl is left for shortcuts but ctrl+alt is
+// typed since that's how some terminals send AltGr
+fn typed_char(key: &KeyEvent) -> Option<char> {
+    match key.code {
+        KeyCode::Char(c)
+            if !key.modifiers.contains(KeyModifiers::CONTROL)
+                || key.modifiers.contains(KeyModifiers::ALT) =>
+        {
+            Some(c)
+        }
+        _ => None,
+    }
+}
+
+// the text is kept split around the cursor, with whatever is selected in between
 #[derive(Default, Clone)]
 pub struct TextInputField {
     left_buffer: String,
-    _selection_buffer: String,
+    selection_buffer: String,
     right_buffer: String,
+    cursor_before_selection: bool, // selecting leftwards puts the cursor at the selection's start
+    multi_line: bool,              // whether pasted text keeps its new lines
 }
 
 impl TextInputField {
-    pub fn draw_unselected(&self, frame: &mut Frame, area: Rect) {
-        let left_span = Span::styled(self.left_buffer.as_str(), Style::default());
-        let right_span = Span::styled(self.right_buffer.as_str(), Style::default());
-        let text_line = Line::from(vec![left_span, right_span]);
+    pub fn from_string(text: &str) -> Self {
+        Self {
+            left_buffer: text.to_string(),
+            ..Default::default()
+        }
+    }
 
-        frame.render_widget(text_line, area);
+    pub fn multi_line(self) -> Self {
+        Self {
+            multi_line: true,
+            ..self
+        }
+    }
+
+    pub fn draw_unselected(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Line::from(self.text()), area);
     }
+
     pub fn draw_selected(&self, frame: &mut Frame, area: Rect) {
         let left_span = Span::styled(self.left_buffer.as_str(), Style::default());
+        let selection_span = Span::styled(self.selection_buffer.as_str(), Style::new().reversed());
         let right_span = Span::styled(self.right_buffer.as_str(), Style::default());
-        let left_span_width = left_span.width() as f64;
-        let text_line = Line::from(vec![left_span, right_span]);
+        let cursor_x = match self.cursor_before_selection {
+            true => left_span.width(),
+            false => left_span.width() + selection_span.width(),
+        };
+        let text_line = Line::from(vec![left_span, selection_span, right_span]);
 
         frame.render_widget(text_line, area);
-        frame.set_cursor_position((area.x + left_span_width as u16, area.y));
+        frame.set_cursor_position((area.x + cursor_x as u16, area.y));
     }
 
-    pub fn cursor_left(&mut self) {
-        if let Some(char_to_right) = self.left_buffer.pop() {
-            let mut new_right_buffer = char_to_right.to_string();
-            let old_right_buffer = std::mem::take(&mut self.right_buffer);
-            new_right_buffer.push_str(old_right_buffer.as_str());
-            self.right_buffer = new_right_buffer;
+    // draws the text wrapped to the area, scrolled so the line with the cursor is showing
+    pub fn draw_area(&self, frame: &mut Frame, area: Rect, selected: bool) {
+        // a terminal too short for the form leaves no room for it
+        if area.height == 0 {
+            return;
+        }
+        let text = self.text();
+        // a column is kept free so the cursor fits after a full line
+        let lines = wrap::wrap(&text, area.width.saturating_sub(1) as usize);
+        let (cursor_row, cursor_column) = wrap::position(&text, &lines, self.cursor_offset());
+        let scroll = (cursor_row + 1).saturating_sub(area.height as usize);
+        let selection_start = self.left_buffer.len();
+        let selection_end = selection_start + self.selection_buffer.len();
+        let visible: Vec<Line> = lines
+            .iter()
+            .skip(scroll)
+            .map(|&(start, end)| {
+                // the part of the line that's selected, if any
+                let from = selection_start.clamp(start, end);
+                let to = selection_end.clamp(start, end);
+                Line::from(vec![
+                    Span::from(&text[start..from]),
+                    Span::from(&text[from..to]).reversed(),
+                    Span::from(&text[to..end]),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(visible), area);
+        if selected {
+            frame.set_cursor_position((
+                area.x + cursor_column as u16,
+                area.y + (cursor_row - scroll) as u16,
+            ));
         }
     }
 
-    pub fn cursor_right(&mut self) {
-        match self.right_buffer.len() {
-            0 => (),
-            1 => {
-                let char_to_left = self.right_buffer.pop().unwrap();
-                self.left_buffer.push(char_to_left);
+    // readline style editing, with shift+arrows selecting and the clipboard shared by every
+    // field. returns false for keys it doesn't use so the caller can handle them
+    pub fn handle_key(&mut self, key: &KeyEvent, clipboard: &mut String) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Left if shift => self.select_left(),
+            KeyCode::Right if shift => self.select_right(),
+            KeyCode::Left if ctrl || alt => self.word_left(),
+            KeyCode::Right if ctrl || alt => self.word_right(),
+            KeyCode::Left => self.cursor_left(),
+            KeyCode::Right => self.cursor_right(),
+            KeyCode::Home if shift => self.select_to_line_start(),
+            KeyCode::End if shift => self.select_to_line_end(),
+            KeyCode::Home => self.line_start(),
+            KeyCode::End => self.line_end(),
+            KeyCode::Backspace if ctrl || alt => self.kill(Self::delete_word_left, clipboard),
+            KeyCode::Backspace => self.pop(),
+            KeyCode::Delete => self.delete_char(),
+            KeyCode::Char('a') if ctrl => self.line_start(),
+            KeyCode::Char('e') if ctrl => self.line_end(),
+            KeyCode::Char('w') if ctrl => self.kill(Self::delete_word_left, clipboard),
+            KeyCode::Char('u') if ctrl => self.kill(Self::delete_to_line_start, clipboard),
+            KeyCode::Char('k') if ctrl => self.kill(Self::delete_to_line_end, clipboard),
+            KeyCode::Char('x') if ctrl && self.has_selection() => {
+                *clipboard = self.take_selection();
+            }
+            KeyCode::Char('c') if ctrl && self.has_selection() => {
+                *clipboard = self.selection_buffer.clone();
+            }
+            KeyCode::Char('v' | 'y') if ctrl => self.paste(clipboard),
+            _ => match typed_char(key) {
+                Some(c) => self.push(c),
+                None => return false,
+            },
+        }
+        true
+    }
+
+    pub fn has_selection(&self) -> bool {
+        !self.selection_buffer.is_empty()
+    }
+
+    // byte offset of the cursor in text()
+    fn cursor_offset(&self) -> usize {
+        match self.cursor_before_selection {
+            true => self.left_buffer.len(),
+            false => self.left_buffer.len() + self.selection_buffer.len(),
+        }
+    }
+
+    // drops the selection and leaves the cursor where it is
+    fn collapse_selection(&mut self) {
+        let selection = std::mem::take(&mut self.selection_buffer);
+        match self.cursor_before_selection {
+            true => self.right_buffer.insert_str(0, &selection),
+            false => self.left_buffer.push_str(&selection),
+        }
+    }
+
+    fn take_selection(&mut self) -> String {
+        std::mem::take(&mut self.selection_buffer)
+    }
+
+    fn select_left(&mut self) {
+        if !self.has_selection() {
+            self.cursor_before_selection = true;
+        }
+        match self.cursor_before_selection {
+            true => {
+                if let Some((offset, _)) = self.left_buffer.grapheme_indices(true).next_back() {
+                    let moved = self.left_buffer.split_off(offset);
+                    self.selection_buffer.insert_str(0, &moved);
+                }
+            }
+            false => {
+                if let Some((offset, _)) = self.selection_buffer.grapheme_indices(true).next_back()
+                {
+                    let moved = self.selection_buffer.split_off(offset);
+                    self.right_buffer.insert_str(0, &moved);
+                }
+            }
+        }
+    }
+
+    fn select_right(&mut self) {
+        if !self.has_selection() {
+            self.cursor_before_selection = false;
+        }
+        match self.cursor_before_selection {
+            true => {
+                if let Some(grapheme) = self.selection_buffer.graphemes(true).next() {
+                    let moved: String = self.selection_buffer.drain(..grapheme.len()).collect();
+                    self.left_buffer.push_str(&moved);
+                }
+            }
+            false => {
+                if let Some(grapheme) = self.right_buffer.graphemes(true).next() {
+                    let moved: String = self.right_buffer.drain(..grapheme.len()).collect();
+                    self.selection_buffer.push_str(&moved);
+                }
             }
-            2.. => {
-                let old_right_buffer = std::mem::take(&mut self.right_buffer);
-                let (char_to_left, new_right_buffer) = old_right_buffer.split_at(1);
-                self.left_buffer.push_str(char_to_left);
-                self.right_buffer = new_right_buffer.to_string();
+        }
+    }
+
+    fn select_to_line_start(&mut self) {
+        while let Some(c) = self.char_before_cursor()
+            && c != '\n'
+        {
+            self.select_left();
+        }
+    }
+
+    fn select_to_line_end(&mut self) {
+        while let Some(c) = self.char_after_cursor()
+            && c != '\n'
+        {
+            self.select_right();
+        }
+    }
+
+    fn char_before_cursor(&self) -> Option<char> {
+        self.text()[..self.cursor_offset()].chars().next_back()
+    }
+
+    fn char_after_cursor(&self) -> Option<char> {
+        self.text()[self.cursor_offset()..].chars().next()
+    }
+
+    // moves the cursor to the same column of the line above, false when it's on the first line
+    pub fn cursor_up(&mut self) -> bool {
+        self.collapse_selection();
+        let Some(line_start) = self.left_buffer.rfind('\n') else {
+            return false;
+        };
+        let column = self.left_buffer[line_start + 1..].width();
+        let above_start = self.left_buffer[..line_start]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1);
+        let offset = Self::column_offset(&self.left_buffer[above_start..line_start], column);
+        self.set_cursor(above_start + offset);
+        true
+    }
+
+    // moves the cursor to the same column of the line below, false when it's on the last line
+    pub fn cursor_down(&mut self) -> bool {
+        self.collapse_selection();
+        let Some(line_end) = self.right_buffer.find('\n') else {
+            return false;
+        };
+        let column = match self.left_buffer.rfind('\n') {
+            Some(line_start) => self.left_buffer[line_start + 1..].width(),
+            None => self.left_buffer.width(),
+        };
+        let below = &self.right_buffer[line_end + 1..];
+        let below = below.split('\n').next().unwrap_or_default();
+        let offset = self.left_buffer.len() + line_end + 1 + Self::column_offset(below, column);
+        self.set_cursor(offset);
+        true
+    }
+
+    // byte offset of the first grapheme at or past the column in line, or the end of the
+    // line when it's shorter
+    fn column_offset(line: &str, column: usize) -> usize {
+        let mut used = 0;
+        for (offset, grapheme) in line.grapheme_indices(true) {
+            if used >= column {
+                return offset;
             }
+            used += grapheme.width();
+        }
+        line.len()
+    }
+
+    fn set_cursor(&mut self, offset: usize) {
+        let text = self.text();
+        let (left, right) = text.split_at(offset);
+        self.left_buffer = left.to_string();
+        self.selection_buffer.clear();
+        self.right_buffer = right.to_string();
+    }
+
+    // the cursor steps over whole grapheme clusters, so an accented letter or an emoji made of
+    // several chars moves and deletes as one. with a selection it goes to that end of it
+    pub fn cursor_left(&mut self) {
+        if self.has_selection() {
+            self.cursor_before_selection = true;
+            self.collapse_selection();
+            return;
+        }
+        if let Some((offset, _)) = self.left_buffer.grapheme_indices(true).next_back() {
+            let moved = self.left_buffer.split_off(offset);
+            self.right_buffer.insert_str(0, &moved);
+        }
+    }
+
+    pub fn cursor_right(&mut self) {
+        if self.has_selection() {
+            self.cursor_before_selection = false;
+            self.collapse_selection();
+            return;
+        }
+        if let Some(grapheme) = self.right_buffer.graphemes(true).next() {
+            let moved: String = self.right_buffer.drain(..grapheme.len()).collect();
+            self.left_buffer.push_str(&moved);
+        }
+    }
+
+    // words are runs of anything but whitespace, jumping over the whitespace before them
+    fn word_left(&mut self) {
+        self.collapse_selection();
+        self.set_cursor(Self::word_start(&self.left_buffer));
+    }
+
+    fn word_right(&mut self) {
+        self.collapse_selection();
+        self.set_cursor(self.left_buffer.len() + Self::word_end(&self.right_buffer));
+    }
+
+    // byte offset where the word before the end of text starts
+    fn word_start(text: &str) -> usize {
+        text.trim_end()
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(offset, c)| offset + c.len_utf8())
+    }
+
+    // byte offset where the first word in text ends
+    fn word_end(text: &str) -> usize {
+        let word_start = text.len() - text.trim_start().len();
+        text[word_start..]
+            .find(char::is_whitespace)
+            .map_or(text.len(), |offset| word_start + offset)
+    }
+
+    fn line_start(&mut self) {
+        self.collapse_selection();
+        let offset = self
+            .left_buffer
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1);
+        self.set_cursor(offset);
+    }
+
+    fn line_end(&mut self) {
+        self.collapse_selection();
+        let offset = self
+            .right_buffer
+            .find('\n')
+            .unwrap_or(self.right_buffer.len());
+        self.set_cursor(self.left_buffer.len() + offset);
+    }
+
+    // runs a delete that returns what it took out and keeps that in the clipboard, like
+    // readline's kill ring with only one slot
+    fn kill(&mut self, delete: fn(&mut Self) -> String, clipboard: &mut String) {
+        self.collapse_selection();
+        let killed = delete(self);
+        if !killed.is_empty() {
+            *clipboard = killed;
         }
     }
 
+    fn delete_word_left(&mut self) -> String {
+        let offset = Self::word_start(&self.left_buffer);
+        self.left_buffer.split_off(offset)
+    }
+
+    fn delete_to_line_start(&mut self) -> String {
+        let offset = self
+            .left_buffer
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1);
+        self.left_buffer.split_off(offset)
+    }
+
+    fn delete_to_line_end(&mut self) -> String {
+        let offset = self
+            .right_buffer
+            .find('\n')
+            .unwrap_or(self.right_buffer.len());
+        self.right_buffer.drain(..offset).collect()
+    }
+
+    // text pasted from the terminal or the clipboard, a single line field gets its lines
+    // joined by spaces. control characters other than new lines are dropped and tabs become
+    // spaces
+    pub fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let text = match self.multi_line {
+            true => text,
+            false => text.trim_end_matches('\n').replace('\n', " "),
+        };
+        let text: String = text
+            .chars()
+            .map(|c| if c == '\t' { ' ' } else { c })
+            .filter(|&c| c == '\n' || !c.is_control())
+            .collect();
+        self.insert(&text);
+    }
+
+    // typing or pasting replaces the selection
+    fn insert(&mut self, text: &str) {
+        self.take_selection();
+        self.left_buffer.push_str(text);
+    }
+
     pub fn push(&mut self, new_char: char) {
+        self.take_selection();
         self.left_buffer.push(new_char);
     }
 
-    pub fn pop(&mut self) -> Option<char> {
-        self.left_buffer.pop()
+    pub fn pop(&mut self) {
+        if self.has_selection() {
+            self.take_selection();
+            return;
+        }
+        if let Some((offset, _)) = self.left_buffer.grapheme_indices(true).next_back() {
+            self.left_buffer.truncate(offset);
+        }
     }
 
     pub fn delete_char(&mut self) {
-        match self.right_buffer.len() {
-            0 => (),
-            1 => {
-                self.right_buffer.pop();
-            }
-            2.. => {
-                let old_right_buffer = std::mem::take(&mut self.right_buffer);
-                let (_, new_right_buffer) = old_right_buffer.split_at(1);
-                self.right_buffer = new_right_buffer.to_string();
-            }
+        if self.has_selection() {
+            self.take_selection();
+            return;
         }
+        if let Some(grapheme) = self.right_buffer.graphemes(true).next() {
+            self.right_buffer.drain(..grapheme.len());
+        }
+    }
+
+    pub fn text(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.left_buffer, self.selection_buffer, self.right_buffer
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn single_line_fields_join_pasted_lines() {
+        let mut input = TextInputField::from_string("a ");
+        input.paste("one\r\ntwo\tthree\n");
+        assert_eq!(input.text(), "a one two three");
     }
 
-    pub fn return_buffer(self) -> String {
-        let Self {
-            mut left_buffer,
-            right_buffer,
-            ..
-        } = self;
-        left_buffer.push_str(right_buffer.as_str());
-        left_buffer
+    #[test]
+    fn multi_line_fields_keep_pasted_lines() {
+        let mut input = TextInputField::default().multi_line();
+        input.paste("one\r\ntwo\x07");
+        assert_eq!(input.text(), "one\ntwo");
+    }
+
+    #[test]
+    fn the_clipboard_is_pasted_like_the_terminal() {
+        let mut clipboard = "one\ntwo".to_string();
+        let mut input = TextInputField::default();
+        input.handle_key(&ctrl('v'), &mut clipboard);
+        assert_eq!(input.text(), "one two");
+
+        let mut input = TextInputField::default().multi_line();
+        input.handle_key(&ctrl('y'), &mut clipboard);
+        assert_eq!(input.text(), "one\ntwo");
     }
 }
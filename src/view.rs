@@ -0,0 +1,124 @@
+use crate::Task;
+use crate::task_status::{Statuses, TaskStatus};
+
+// how the main view shows the list, none of this changes what's saved
+#[derive(Default)]
+pub struct View {
+    pub sort: SortMode,
+    pub tag: Option<String>, // only tasks with this tag and the tasks above them are shown
+    pub status: StatusFilter,
+}
+
+impl View {
+    // whether anything is hidden or reordered, moving around falls back to the plain
+    // stored order when it isn't
+    pub fn is_plain(&self) -> bool {
+        self.sort == SortMode::Stored && self.tag.is_none() && self.status == StatusFilter::All
+    }
+
+    fn matches(&self, task: &Task, statuses: &Statuses) -> bool {
+        self.tag.as_ref().is_none_or(|tag| task.has_tag(tag))
+            && self.status.allows(task.status(), statuses)
+    }
+
+    // a task stays in view when it matches or anything under it does, so matches keep their
+    // place in the tree
+    pub fn shows(&self, task: &Task, statuses: &Statuses) -> bool {
+        self.matches(task, statuses)
+            || task
+                .children()
+                .iter()
+                .any(|child| self.shows(child, statuses))
+    }
+
+    // indices into tasks in the order they're shown, leaving out the ones filtered out
+    pub fn order(&self, tasks: &[Task], statuses: &Statuses) -> Vec<usize> {
+        self.sort
+            .order(tasks, statuses)
+            .into_iter()
+            .filter(|&i| self.shows(&tasks[i], statuses))
+            .collect()
+    }
+}
+
+// which statuses the main view shows, with custom statuses anything between the first status
+// and the done ones counts as in progress
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum StatusFilter {
+    #[default]
+    All,
+    HideDone,
+    InProgress,
+    NotStarted,
+}
+
+impl StatusFilter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::All => "all statuses",
+            Self::HideDone => "hiding done",
+            Self::InProgress => "only in progress",
+            Self::NotStarted => "only not started",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::HideDone,
+            Self::HideDone => Self::InProgress,
+            Self::InProgress => Self::NotStarted,
+            Self::NotStarted => Self::All,
+        }
+    }
+
+    fn allows(self, status: TaskStatus, statuses: &Statuses) -> bool {
+        match self {
+            Self::All => true,
+            Self::HideDone => !statuses.is_done(status),
+            Self::InProgress => !statuses.is_done(status) && status != statuses.initial(),
+            Self::NotStarted => status == statuses.initial(),
+        }
+    }
+}
+
+// orders siblings in the main view, the order they're stored and saved in stays the same
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum SortMode {
+    #[default]
+    Stored,
+    Priority, // most pressing first
+    Status,   // in the statuses' cycle order
+    Due,      // soonest first, tasks without a due date last
+}
+
+impl SortMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Stored => "stored order",
+            Self::Priority => "priority",
+            Self::Status => "status",
+            Self::Due => "due date",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Stored => Self::Priority,
+            Self::Priority => Self::Status,
+            Self::Status => Self::Due,
+            Self::Due => Self::Stored,
+        }
+    }
+
+    // indices into tasks in the order they're shown, ties keep their stored order
+    pub fn order(self, tasks: &[Task], statuses: &Statuses) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..tasks.len()).collect();
+        match self {
+            Self::Stored => (),
+            Self::Priority => order.sort_by_key(|&i| std::cmp::Reverse(tasks[i].priority())),
+            Self::Status => order.sort_by_key(|&i| statuses.order(tasks[i].status())),
+            Self::Due => order.sort_by_key(|&i| (tasks[i].due().is_none(), tasks[i].due())),
+        }
+        order
+    }
+}
@@ -0,0 +1,122 @@
+use crate::Task;
+use crate::task_status::Statuses;
+use ratatui::prelude::*;
+use ratatui::widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState};
+
+// columns each level of nesting is indented by
+const INDENT: u16 = 4;
+
+pub struct TaskRow<'a> {
+    pub task: &'a Task,
+    pub path: Vec<usize>,
+    pub selected: bool,
+}
+
+impl TaskRow<'_> {
+    fn depth(&self) -> u16 {
+        self.path.len().saturating_sub(1) as u16
+    }
+
+    fn indent(&self, width: u16) -> u16 {
+        (self.depth() * INDENT).min(width.saturating_sub(1))
+    }
+
+    // tasks taller than the view are cut short so they can still be drawn
+    fn height(&self, width: u16, max_height: u16) -> u16 {
+        self.task.height(width - self.indent(width)).min(max_height)
+    }
+}
+
+// draws the flattened task rows starting from first_row, only whole tasks are drawn and
+// first_row is moved just enough to keep the selected task on screen
+#[derive(Default)]
+pub struct Viewport {
+    first_row: usize,
+}
+
+impl Viewport {
+    pub fn draw(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        rows: &[TaskRow],
+        statuses: &Statuses,
+        search: Option<&str>,
+    ) {
+        // descriptions wrap to the width left for them, which is one less once the heights
+        // call for a scrollbar
+        let heights_at = |width: u16| -> Vec<u16> {
+            rows.iter()
+                .map(|row| row.height(width, area.height))
+                .collect()
+        };
+        let mut heights = heights_at(area.width);
+        let overflows =
+            heights.iter().map(|&height| height as usize).sum::<usize>() > area.height as usize;
+
+        let [task_area, scrollbar_area] = match overflows {
+            true => Layout::horizontal([Constraint::Fill(1), Constraint::Length(1)]).areas(area),
+            false => [area, Rect::default()],
+        };
+        if overflows {
+            heights = heights_at(task_area.width);
+        }
+        let total_height: usize = heights.iter().map(|&height| height as usize).sum();
+
+        let selected_row = rows.iter().position(|row| row.selected);
+        self.scroll_to(selected_row, &heights, task_area.height);
+
+        let mut y = task_area.y;
+        let first_row = self.first_row.min(rows.len());
+        for (row, &height) in rows[first_row..].iter().zip(&heights[first_row..]) {
+            if y + height > task_area.bottom() {
+                break;
+            }
+            let indent = row.indent(task_area.width);
+            let row_area = Rect::new(task_area.x + indent, y, task_area.width - indent, height);
+            row.task
+                .draw_block(frame, row_area, row.selected, statuses, search);
+            y += height;
+        }
+
+        if overflows {
+            let offset: usize = heights[..self.first_row]
+                .iter()
+                .map(|&height| height as usize)
+                .sum();
+            // the scrollbar's content length counts scroll positions, not rows
+            let scroll_positions = total_height - task_area.height as usize + 1;
+            let mut scrollbar_state = ScrollbarState::new(scroll_positions)
+                .position(offset)
+                .viewport_content_length(task_area.height as usize);
+            frame.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                scrollbar_area,
+                &mut scrollbar_state,
+            );
+        }
+    }
+
+    fn scroll_to(&mut self, selected_row: Option<usize>, heights: &[u16], view_height: u16) {
+        let rows_height =
+            |rows: &[u16]| -> usize { rows.iter().map(|&height| height as usize).sum() };
+
+        self.first_row = self.first_row.min(heights.len().saturating_sub(1));
+        if let Some(selected_row) = selected_row {
+            if selected_row < self.first_row {
+                self.first_row = selected_row;
+            }
+            while self.first_row < selected_row
+                && rows_height(&heights[self.first_row..=selected_row]) > view_height as usize
+            {
+                self.first_row += 1;
+            }
+        }
+        // pull the view back up when rows were removed and there's empty space at the bottom
+        while self.first_row > 0
+            && rows_height(&heights[self.first_row - 1..]) <= view_height as usize
+        {
+            self.first_row -= 1;
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// splits text into the byte ranges it's drawn as at width columns (wide characters take two),
+// breaking after a space where it can and inside a word when it's longer than a line. spaces
+// stay on the line they end so every byte but the newlines belongs to exactly one line
+pub fn wrap(text: &str, width: usize) -> Vec<(usize, usize)> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        let mut start = line_start;
+        let end = line_start + line.len();
+        loop {
+            let rest = &text[start..end];
+            let Some(cut) = overflow(rest, width) else {
+                lines.push((start, end));
+                break;
+            };
+            let break_at = match rest[..cut].rfind(' ') {
+                _ if rest[cut..].starts_with(' ') => cut + 1,
+                Some(space) if space > 0 => space + 1,
+                _ => cut,
+            };
+            lines.push((start, start + break_at));
+            start += break_at;
+        }
+        line_start = end + 1;
+    }
+    lines
+}
+
+// byte offset of the first grapheme that doesn't fit in width columns, a line always gets at
+// least one grapheme even when it's wider than the whole line
+fn overflow(line: &str, width: usize) -> Option<usize> {
+    let mut used = 0;
+    line.grapheme_indices(true)
+        .find(|(_, grapheme)| {
+            used += grapheme.width();
+            used > width
+        })
+        .map(|(offset, grapheme)| match offset {
+            0 => grapheme.len(),
+            _ => offset,
+        })
+        .filter(|&offset| offset < line.len())
+}
+
+// the line and column the byte offset is drawn at, an offset where one line wraps into the
+// next is drawn at the start of the next one
+pub fn position(text: &str, lines: &[(usize, usize)], offset: usize) -> (usize, usize) {
+    let row = lines
+        .iter()
+        .rposition(|&(start, _)| start <= offset)
+        .unwrap_or(0);
+    let column = lines
+        .get(row)
+        .map_or(0, |&(start, _)| text[start..offset].width());
+    (row, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrapped(text: &str, width: usize) -> Vec<&str> {
+        wrap(text, width)
+            .into_iter()
+            .map(|(start, end)| &text[start..end])
+            .collect()
+    }
+
+    #[test]
+    fn short_lines_are_left_alone() {
+        assert_eq!(wrapped("", 10), [""]);
+        assert_eq!(wrapped("hello world", 20), ["hello world"]);
+        assert_eq!(wrapped("hello world", 11), ["hello world"]);
+    }
+
+    #[test]
+    fn breaks_after_spaces() {
+        assert_eq!(wrapped("hello big world", 10), ["hello big ", "world"]);
+        assert_eq!(wrapped("hello world", 8), ["hello ", "world"]);
+        // a space right where the line runs out stays at its end
+        assert_eq!(wrapped("hello world", 5), ["hello ", "world"]);
+    }
+
+    #[test]
+    fn long_words_are_split() {
+        assert_eq!(wrapped("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrapped("a abcdefghij", 4), ["a ", "abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn new_lines_start_new_lines() {
+        assert_eq!(wrapped("a\n\nb", 10), ["a", "", "b"]);
+        assert_eq!(wrapped("ab\n", 10), ["ab", ""]);
+    }
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(wrapped("日本語テキスト", 5), ["日本", "語テ", "キス", "ト"]);
+        assert_eq!(wrapped("日本 語", 4), ["日本 ", "語"]);
+        // a character wider than the line still gets a line of its own
+        assert_eq!(wrapped("日本", 1), ["日", "本"]);
+        assert_eq!(wrapped("ab", 0), ["a", "b"]);
+    }
+
+    #[test]
+    fn graphemes_are_never_split() {
+        let accented = "e\u{301}e\u{301}e\u{301}";
+        assert_eq!(wrapped(accented, 2), ["e\u{301}e\u{301}", "e\u{301}"]);
+        let family = "👨\u{200d}👩\u{200d}👧";
+        assert_eq!(wrapped(&format!("{family}{family}"), 2), [family, family]);
+    }
+
+    #[test]
+    fn every_byte_but_new_lines_is_on_one_line() {
+        for (text, width) in [
+            ("the quick  brown fox\njumps   over", 6),
+            ("日本語 テキスト と text", 5),
+            ("   leading spaces", 4),
+        ] {
+            let joined = wrapped(text, width).concat();
+            assert_eq!(joined, text.replace('\n', ""), "{text:?} at {width}");
+        }
+    }
+
+    #[test]
+    fn positions_are_rows_and_display_columns() {
+        let text = "hello world";
+        let lines = wrap(text, 8);
+        assert_eq!(position(text, &lines, 0), (0, 0));
+        assert_eq!(position(text, &lines, 5), (0, 5));
+        // where one line wraps into the next is the start of the next
+        assert_eq!(position(text, &lines, 6), (1, 0));
+        assert_eq!(position(text, &lines, text.len()), (1, 5));
+
+        let text = "ab\n日本語";
+        let lines = wrap(text, 4);
+        assert_eq!(position(text, &lines, 2), (0, 2));
+        assert_eq!(position(text, &lines, 3), (1, 0));
+        assert_eq!(position(text, &lines, 6), (1, 2));
+        assert_eq!(position(text, &lines, 9), (2, 0));
+        assert_eq!(position(text, &lines, text.len()), (2, 2));
+    }
+}